@@ -0,0 +1,383 @@
+use std::collections::HashSet;
+
+use rand::{seq::IndexedRandom, Rng};
+
+use crate::{game::{Board, Point, ShipType, StrikeOutcome}, helpers::GameRng, weapons::{Action, Arsenal, Weapon}};
+
+// Number of ways the remaining fleet could cover each square, indexed as [y][x].
+pub type Heatmap = Vec<Vec<u64>>;
+
+// How much more a placement counts for each unresolved hit it covers, so damaged ships are finished off first.
+const HIT_WEIGHT: u64 = 20;
+// Placements covering more unresolved hits than this are all weighted the same, so that the weights of a long ship with
+// many hits can't overflow however the heatmap sums them.
+const MAX_WEIGHTED_HITS: u32 = 6;
+
+// A computer player decides where to fire next. Strategies are only ever handed the computer's own board, so everything
+// they know about the opponent has to come from the impacts they have already recorded, never from the opponent's ships.
+// Any random choices must come from the game's generator, so games can be reproduced from their seed.
+pub trait Strategy {
+    fn next_shot(&mut self, board: &Board, rng: &mut GameRng) -> Point;
+
+    // The heatmap behind the most recent shot, for strategies that compute one.
+    fn heatmap(&self) -> Option<&Heatmap> {
+        None
+    }
+}
+
+// Picks every shot of a turn before any of them land, as salvo rules require. Squares already picked are treated as
+// misses while choosing the rest, so the strategy spreads its shots out instead of picking the same square again.
+pub fn choose_salvo(strategy: &mut dyn Strategy, board: &Board, shots: usize, rng: &mut GameRng) -> Vec<Point> {
+    if shots == 1 {
+        return vec![strategy.next_shot(board, rng)];
+    }
+
+    let mut planning = board.clone();
+    let mut points = Vec::new();
+    for _ in 0..shots {
+        let point = strategy.next_shot(&planning, rng);
+        planning.record_strike(point, &StrikeOutcome::Miss);
+        points.push(point);
+    }
+    points
+}
+
+// Decides whether to use a special weapon this turn instead of firing, and where to aim it. Weapons are saved for hunting:
+// while a damaged ship is still afloat, the strategy's own shots finish it off. Otherwise whatever is left is aimed at the
+// square the strategy would have fired at, with the weapons that strike most squares used up first.
+pub fn choose_action(strategy: &mut dyn Strategy, board: &Board, arsenal: &Arsenal, rng: &mut GameRng) -> Option<Action> {
+    if arsenal.is_empty() || !unresolved_hits(board).is_empty() {
+        return None;
+    }
+    let point = strategy.next_shot(board, rng);
+    [Weapon::ClusterBomb, Weapon::Torpedo, Weapon::Radar].into_iter().find(|weapon| arsenal.has(*weapon)).map(|weapon| weapon.aimed_at(point))
+}
+
+// Fires at a uniformly random square that has not been struck yet.
+#[derive(Debug, Default)]
+pub struct RandomStrategy;
+
+impl Strategy for RandomStrategy {
+    fn next_shot(&mut self, board: &Board, rng: &mut GameRng) -> Point {
+        let candidates = unstruck_points(board);
+        candidates[rng.random_range(0..candidates.len())]
+    }
+}
+
+// Hunts with a checkerboard pattern spaced by the smallest ship still afloat, and once it scores a hit, probes the
+// neighbouring squares until it finds the ship's axis, then follows that axis until the ship is reported sunk.
+#[derive(Debug, Default)]
+pub struct HuntTargetStrategy;
+
+impl Strategy for HuntTargetStrategy {
+    fn next_shot(&mut self, board: &Board, rng: &mut GameRng) -> Point {
+        let hits = unresolved_hits(board);
+        let ruled_out = ruled_out(board);
+        let targets = target_candidates(board, &hits, &ruled_out);
+        if let Some(point) = targets.choose(rng) {
+            return *point;
+        }
+
+        let spacing = afloat_ships(board).iter().map(|ship_type| ship_type.length).min().unwrap_or(1);
+        let candidates = open_points(board, &ruled_out);
+        let parity_candidates: Vec<Point> = candidates.iter().filter(|point| (point.x + point.y) % spacing == 0).copied().collect();
+        match parity_candidates.choose(rng) {
+            Some(point) => *point,
+            None => candidates[rng.random_range(0..candidates.len())],
+        }
+    }
+}
+
+// Every turn, counts how many legal placements of each ship still afloat would cover each square, given the misses and
+// sunk wrecks seen so far, and fires at the square with the highest count. Placements over unresolved hits are weighted
+// heavily so that it finishes off damaged ships before hunting again.
+#[derive(Debug, Default)]
+pub struct ProbabilityStrategy {
+    last_heatmap: Heatmap,
+}
+
+impl Strategy for ProbabilityStrategy {
+    fn next_shot(&mut self, board: &Board, rng: &mut GameRng) -> Point {
+        self.last_heatmap = placement_heatmap(board);
+        // Only squares open to a placement are ever counted, so any square with the highest count is worth firing at and
+        // there's no need to work out which squares are open first. Should no placement be left at all, every open square
+        // is as good as any other.
+        let heatmap = &self.last_heatmap;
+        let best = heatmap.iter().flatten().copied().max().unwrap_or(0);
+        let best_candidates: Vec<Point> = if best > 0 {
+            (0..board.height).flat_map(|y| (0..board.width).map(move |x| Point { x, y }))
+                .filter(|point| heatmap[point.y as usize][point.x as usize] == best)
+                .collect()
+        } else {
+            open_points(board, &ruled_out(board))
+        };
+        *best_candidates.choose(rng).unwrap()
+    }
+
+    fn heatmap(&self) -> Option<&Heatmap> {
+        Some(&self.last_heatmap)
+    }
+}
+
+// Builds the placement-count heatmap used by ProbabilityStrategy. Squares already fired at are always zero.
+pub fn placement_heatmap(board: &Board) -> Heatmap {
+    let is_hit = unresolved_grid(board);
+    let (width, height) = (board.width as usize, board.height as usize);
+
+    // Misses, the squares of sunk ships, and any squares ruled out as water can't hold any of the remaining ships.
+    let mut blocked = ruled_out_around(board, &is_hit);
+    for impact in &board.impacts {
+        if !impact.hit || !is_hit[impact.coords.y as usize][impact.coords.x as usize] {
+            blocked[impact.coords.y as usize][impact.coords.x as usize] = true;
+        }
+    }
+
+    // Ships of the same length cover the board in exactly the same ways, so each length is only counted once and then
+    // multiplied by the number of such ships still afloat.
+    let mut lengths: Vec<(usize, u64)> = Vec::new();
+    for (ship_type, afloat) in afloat_classes(board) {
+        match lengths.iter_mut().find(|(length, _)| *length == ship_type.length as usize) {
+            Some((_, count)) => *count += afloat,
+            None if afloat > 0 => lengths.push((ship_type.length as usize, afloat)),
+            None => {},
+        }
+    }
+
+    // This runs for every shot the hard computer takes, so rather than visiting every square of every placement, each row
+    // and column is first turned into running counts of its blocked squares and hits, from which any run of squares can
+    // be checked at once. Each placement's weight is noted where its run starts and ends, and once every length has been
+    // through the line, the weights are summed into its squares in a single pass.
+    let weights: Vec<(usize, Vec<u64>)> = lengths.iter()
+        .map(|&(length, ships)| (length, (0..=MAX_WEIGHTED_HITS).map(|hits| HIT_WEIGHT.pow(hits) * ships).collect()))
+        .collect();
+    let mut heatmap = vec![vec![0; width]; height];
+    let longest_line = width.max(height);
+    // Counts of the blocked squares and hits before each offset along the line, so index 0 is always zero.
+    let (mut blocked_before, mut hits_before) = (vec![0u32; longest_line + 1], vec![0u32; longest_line + 1]);
+    let (mut starting, mut ending) = (vec![0u64; longest_line], vec![0u64; longest_line]);
+    for vertical in [false, true] {
+        let (lines, line_length) = if vertical { (width, height) } else { (height, width) };
+        for line in 0..lines {
+            for offset in 0..line_length {
+                let (x, y) = if vertical { (line, offset) } else { (offset, line) };
+                blocked_before[offset + 1] = blocked_before[offset] + blocked[y][x] as u32;
+                hits_before[offset + 1] = hits_before[offset] + is_hit[y][x] as u32;
+            }
+            if blocked_before[line_length] == line_length as u32 {
+                continue;
+            }
+            starting[..line_length].fill(0);
+            ending[..line_length].fill(0);
+
+            // A ship of length 1 is the same placement either way round.
+            for (length, weights) in weights.iter().filter(|(length, _)| !(vertical && *length == 1) && *length <= line_length) {
+                for start in 0..=line_length - length {
+                    let end = start + length;
+                    if blocked_before[end] == blocked_before[start] {
+                        let weight = weights[(hits_before[end] - hits_before[start]).min(MAX_WEIGHTED_HITS) as usize];
+                        starting[start] += weight;
+                        ending[end - 1] += weight;
+                    }
+                }
+            }
+
+            let mut running = 0;
+            for offset in 0..line_length {
+                running += starting[offset];
+                let (x, y) = if vertical { (line, offset) } else { (offset, line) };
+                heatmap[y][x] += running;
+                running -= ending[offset];
+            }
+        }
+    }
+
+    // Hits are already known to hold a ship, so there's no point counting them.
+    for (heat_row, hit_row) in heatmap.iter_mut().zip(&is_hit) {
+        for (heat, _) in heat_row.iter_mut().zip(hit_row).filter(|(_, hit)| **hit) {
+            *heat = 0;
+        }
+    }
+
+    heatmap
+}
+
+// Every square on the board that has not been fired at yet, in row order.
+pub fn unstruck_points(board: &Board) -> Vec<Point> {
+    let mut points = Vec::with_capacity(board.open_squares());
+    for y in 0..board.height {
+        for x in 0..board.width {
+            let point = Point { x, y };
+            if !board.struck(&point) {
+                points.push(point);
+            }
+        }
+    }
+    points
+}
+
+// The squares worth firing at: those not struck yet and not ruled out as water. Should the opponent's answers ever leave
+// nothing worth firing at, every unstruck square is fair game again.
+fn open_points(board: &Board, ruled_out: &[Vec<bool>]) -> Vec<Point> {
+    let points = unstruck_points(board);
+    let open: Vec<Point> = points.iter().filter(|point| !ruled_out[point.y as usize][point.x as usize]).copied().collect();
+    if open.is_empty() { points } else { open }
+}
+
+// Squares known to be water without having been fired at, indexed as [y][x]. Only ships kept apart give anything away:
+// nothing can lie next to a sunk wreck, and since ships are straight, nothing can lie diagonally next to any hit either.
+pub fn ruled_out(board: &Board) -> Vec<Vec<bool>> {
+    ruled_out_around(board, &unresolved_grid(board))
+}
+
+// ruled_out, for when the unresolved hits have already been worked out.
+fn ruled_out_around(board: &Board, unresolved: &[Vec<bool>]) -> Vec<Vec<bool>> {
+    let mut ruled_out = vec![vec![false; board.width as usize]; board.height as usize];
+    if !board.no_touch {
+        return ruled_out;
+    }
+
+    for impact in board.impacts.iter().filter(|impact| impact.hit) {
+        let wrecked = !unresolved[impact.coords.y as usize][impact.coords.x as usize];
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let point = Point { x: impact.coords.x + dx, y: impact.coords.y + dy };
+                let diagonal = dx != 0 && dy != 0;
+                if (diagonal || (wrecked && (dx, dy) != (0, 0))) && board.in_bounds(&point) {
+                    ruled_out[point.y as usize][point.x as usize] = true;
+                }
+            }
+        }
+    }
+    ruled_out
+}
+
+// Ships in the opponent's fleet that have not been reported sunk yet. A fleet can have several ships of the same type, so
+// each sinking only accounts for one of them.
+pub fn afloat_ships(board: &Board) -> Vec<ShipType> {
+    afloat_classes(board).into_iter().flat_map(|(ship_type, count)| (0..count).map(|_| ship_type.clone())).collect()
+}
+
+// How many ships of each class in the opponent's fleet have not been reported sunk yet, without copying the classes.
+fn afloat_classes(board: &Board) -> Vec<(&ShipType, u64)> {
+    let mut afloat: Vec<(&ShipType, u64)> = board.fleet.classes.iter().map(|(ship_type, count)| (ship_type, *count as u64)).collect();
+    for sunk in board.impacts.iter().filter_map(|impact| impact.sunk.as_ref()) {
+        if let Some((_, count)) = afloat.iter_mut().find(|(ship_type, count)| *ship_type == sunk && *count > 0) {
+            *count -= 1;
+        }
+    }
+    afloat
+}
+
+// Hits which cannot be attributed to a ship that has already been sunk. A sinking is only reported at the square that
+// finished the ship off, so the rest of the wreck is worked out by finding a straight run of hits through that square
+// which is long enough to hold the ship, and removing the squares closest to it.
+pub fn unresolved_hits(board: &Board) -> HashSet<Point> {
+    let hits = unresolved_grid(board);
+    board.impacts.iter()
+        .map(|impact| impact.coords)
+        .filter(|point| hits[point.y as usize][point.x as usize])
+        .collect()
+}
+
+// unresolved_hits as a grid, indexed as [y][x]. It's worked out on a grid rather than in a set, and the computer keeps
+// it that way, since it does this for every shot it takes.
+fn unresolved_grid(board: &Board) -> Vec<Vec<bool>> {
+    let mut hits = vec![vec![false; board.width as usize]; board.height as usize];
+    for impact in board.impacts.iter().filter(|impact| impact.hit) {
+        hits[impact.coords.y as usize][impact.coords.x as usize] = true;
+    }
+
+    let mut sinkings: Vec<(Point, usize)> = board.impacts.iter()
+        .filter_map(|impact| impact.sunk.as_ref().map(|ship_type| (impact.coords, ship_type.length as usize)))
+        .collect();
+    sinkings.sort_by_key(|(point, _)| (point.y, point.x));
+
+    for (point, length) in sinkings {
+        let mut wreck = vec![point];
+        for (dx, dy) in [(1, 0), (0, 1)] {
+            let run = run_through(&hits, point, dx, dy);
+            if run.len() >= length && (wreck.len() < length || run.len() < wreck.len()) {
+                wreck = run;
+            }
+        }
+        wreck.sort_by_key(|p| (p.x - point.x).abs() + (p.y - point.y).abs());
+        for p in wreck.iter().take(length) {
+            hits[p.y as usize][p.x as usize] = false;
+        }
+    }
+    hits
+}
+
+// The contiguous run of hits along the given axis which passes through the point.
+fn run_through(hits: &[Vec<bool>], point: Point, dx: i32, dy: i32) -> Vec<Point> {
+    let is_hit = |point: Point| hits.get(point.y as usize).and_then(|row| row.get(point.x as usize)).copied().unwrap_or(false);
+    let mut run = vec![point];
+    for step in [1, -1] {
+        let mut next = Point { x: point.x + dx * step, y: point.y + dy * step };
+        while is_hit(next) {
+            run.push(next);
+            next = Point { x: next.x + dx * step, y: next.y + dy * step };
+        }
+    }
+    run
+}
+
+// Squares worth firing at to finish off a damaged ship. If two hits line up, the open ends of that line are preferred;
+// otherwise every unstruck orthogonal neighbour of a hit is a candidate. Squares ruled out as water never are.
+fn target_candidates(board: &Board, hits: &HashSet<Point>, ruled_out: &[Vec<bool>]) -> Vec<Point> {
+    let mut line_ends = Vec::new();
+    let mut neighbours = Vec::new();
+
+    // A set iterates in a different order every run, so the hits are sorted to keep seeded games reproducible.
+    let mut ordered: Vec<&Point> = hits.iter().collect();
+    ordered.sort_by_key(|point| (point.y, point.x));
+    for hit in ordered {
+        for (dx, dy) in [(1, 0), (0, 1)] {
+            let lined_up = hits.contains(&Point { x: hit.x + dx, y: hit.y + dy })
+                || hits.contains(&Point { x: hit.x - dx, y: hit.y - dy });
+            for step in [1, -1] {
+                let mut next = Point { x: hit.x + dx * step, y: hit.y + dy * step };
+                if lined_up {
+                    while hits.contains(&next) {
+                        next = Point { x: next.x + dx * step, y: next.y + dy * step };
+                    }
+                }
+                if board.in_bounds(&next) && !board.struck(&next) && !ruled_out[next.y as usize][next.x as usize] {
+                    if lined_up {
+                        line_ends.push(next);
+                    } else {
+                        neighbours.push(next);
+                    }
+                }
+            }
+        }
+    }
+
+    if line_ends.is_empty() { neighbours } else { line_ends }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fleet::Fleet, testing::point};
+
+    #[test]
+    fn many_hits_on_a_long_ship_do_not_overflow_the_heatmap() {
+        let fleet = Fleet::new("Long", &[("Long".to_string(), 18, 2)]).unwrap();
+        let mut board = Board::new(26, 26, fleet);
+        for x in 0..15 {
+            board.record_strike(point(x, 0), &StrikeOutcome::Hit);
+        }
+        let heatmap = placement_heatmap(&board);
+        assert!(heatmap[0][15] > heatmap[1][15]);
+        assert_eq!(heatmap[0][0], 0);
+    }
+
+    #[test]
+    fn the_heatmap_counts_every_placement() {
+        let fleet = Fleet::new("Pair", &[("Destroyer".to_string(), 2, 1)]).unwrap();
+        let heatmap = placement_heatmap(&Board::new(3, 3, fleet));
+        // A corner is covered by two placements, an edge by three and the centre by four.
+        assert_eq!(heatmap, vec![vec![2, 3, 2], vec![3, 4, 3], vec![2, 3, 2]]);
+    }
+}
@@ -0,0 +1,73 @@
+use std::time::Instant;
+
+use battleship_rust::{rules::Rules, simulation::{percentile, play_games, SimulationReport}};
+
+use crate::{cli::SimulateArgs, create_strategy};
+
+// Batches of games between two computer players, summarised so strategies can be compared against each other.
+
+// The longest bar drawn in the histogram, in characters.
+const HISTOGRAM_WIDTH: usize = 50;
+// Roughly how many rows the histogram is split into.
+const HISTOGRAM_ROWS: u32 = 20;
+
+pub fn simulate(args: SimulateArgs, seed: u64) {
+    let (width, height) = args.size;
+    let levels = [args.player1, args.player2];
+    let rules = Rules { shoot_again: args.shoot_again, no_touch: args.no_touch, reveal_water: args.reveal_water, advanced: args.advanced, ..args.rules.into() };
+    println!("Simulating {} games between {:?} and {:?} with the {} fleet on a {}x{} board, under {}...", args.games, args.player1, args.player2, args.fleet, width, height, rules);
+
+    let started = Instant::now();
+    let report = play_games(args.games, width, height, &args.fleet, rules, || levels.map(|level| create_strategy(Some(level.into()))), seed);
+    let elapsed = started.elapsed().as_secs_f64();
+    println!("Played {} games in {:.2}s ({:.0} games a second).\n", args.games, elapsed, args.games as f64 / elapsed);
+
+    println!("{:<20} {:>16}   {:>6} {:>6} {:>6}", "", "Wins", "Mean", "Median", "95th");
+    for player in 1..=2 {
+        print_row(&format!("Player {} ({:?})", player, levels[player - 1]), &report, Some(player as i32));
+    }
+    print_row("All games", &report, None);
+    println!();
+
+    println!("Shots taken by the winner:");
+    for line in histogram(&report.shots_to_win(None)) {
+        println!("{}", line);
+    }
+}
+
+// One line of the summary table: how many games were won, and how many shots winning them took.
+fn print_row(label: &str, report: &SimulationReport, player: Option<i32>) {
+    let shots = report.shots_to_win(player);
+    let wins = format!("{} ({:.1}%)", shots.len(), shots.len() as f64 / report.games.len().max(1) as f64 * 100.0);
+    match (percentile(&shots, 0.5), percentile(&shots, 0.95)) {
+        (Some(median), Some(p95)) => {
+            let mean = shots.iter().map(|&shot| shot as f64).sum::<f64>() / shots.len() as f64;
+            println!("{:<20} {:>16}   {:>6.1} {:>6} {:>6}", label, wins, mean, median, p95);
+        },
+        _ => println!("{:<20} {:>16}   {:>6} {:>6} {:>6}", label, wins, "-", "-", "-"),
+    }
+}
+
+// Groups sorted shot counts into equally sized ranges, with a bar for each range scaled to the most common one.
+fn histogram(shots: &[u32]) -> Vec<String> {
+    let (Some(&fewest), Some(&most)) = (shots.first(), shots.last()) else {
+        return Vec::new();
+    };
+    let bucket = (most - fewest) / HISTOGRAM_ROWS + 1;
+    let mut counts = vec![0; ((most - fewest) / bucket + 1) as usize];
+    for shot in shots {
+        counts[((shot - fewest) / bucket) as usize] += 1;
+    }
+
+    let largest = counts.iter().copied().max().unwrap_or(1);
+    let label_width = most.to_string().len();
+    counts.iter().enumerate().map(|(index, &count)| {
+        let start = fewest + index as u32 * bucket;
+        let range = if bucket == 1 {
+            format!("{:>width$}", start, width = label_width * 2 + 1)
+        } else {
+            format!("{:>width$}-{:<width$}", start, start + bucket - 1, width = label_width)
+        };
+        format!("{} | {} {}", range, "#".repeat(count * HISTOGRAM_WIDTH / largest), count)
+    }).collect()
+}
@@ -0,0 +1,249 @@
+use battleship_rust::{enums::{Choice, Difficulty, OpponentChoice, MAX_BOARD_SIZE, MIN_BOARD_SIZE}, fleet::Fleet, notation::Notation, rules::Rules};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+// The command line. Running the game without a subcommand asks how to start, and any setting left off the command line is
+// asked for interactively, so scripted games can skip as many prompts as they like.
+#[derive(Debug, Parser)]
+#[command(version, about = "Battleship, implemented in Rust.")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// Seed the game's random choices with this number, to play a game again exactly; a new seed is picked otherwise
+    #[arg(long, global = true)]
+    pub seed: Option<u64>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Start a new game at this terminal
+    Play(PlayArgs),
+    /// Carry on with a saved game
+    Resume {
+        /// The save file to load, asked for when left out
+        file: Option<String>,
+    },
+    /// Watch a recorded game again
+    Replay {
+        /// The game record to load, asked for when left out
+        file: Option<String>,
+    },
+    /// Play many games between two computer players and report how each side did
+    Simulate(SimulateArgs),
+    /// Wait for another player to join a game over the network
+    Host(HostArgs),
+    /// Join a game hosted over the network
+    Join {
+        /// The host's address as HOST:PORT, asked for when left out
+        address: Option<String>,
+        /// Place your ships automatically, or by hand if set to false; asked for when left out
+        #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+        auto_place: Option<bool>,
+    },
+}
+
+#[derive(Debug, Default, Args)]
+pub struct PlayArgs {
+    /// Who to play against
+    #[arg(long, value_enum)]
+    pub opponent: Option<Opponent>,
+    /// How well the computer plays; implies playing against the computer
+    #[arg(long, value_enum)]
+    pub difficulty: Option<Level>,
+    /// Which players' ships to place automatically; the others place theirs by hand
+    #[arg(long, value_enum)]
+    pub auto_place: Option<AutoPlace>,
+    #[command(flatten)]
+    pub board: BoardArgs,
+}
+
+#[derive(Debug, Default, Args)]
+pub struct HostArgs {
+    /// The port to listen on
+    #[arg(long)]
+    pub port: Option<u16>,
+    /// Place your ships automatically, or by hand if set to false; asked for when left out
+    #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+    pub auto_place: Option<bool>,
+    #[command(flatten)]
+    pub board: BoardArgs,
+}
+
+#[derive(Debug, Default, Args)]
+pub struct BoardArgs {
+    /// The fleet each player places: standard, classic, russian, or a custom one such as "Frigate 3 2, Corvette 2 3"
+    #[arg(long, value_parser = parse_fleet)]
+    pub fleet: Option<Fleet>,
+    /// The board size as WIDTHxHEIGHT
+    #[arg(long, value_parser = parse_size_arg)]
+    pub size: Option<(i32, i32)>,
+    /// How squares are named
+    #[arg(long, value_enum)]
+    pub notation: Option<NotationArg>,
+    /// The rules to play by: one shot a turn, or one for each ship still afloat
+    #[arg(long, value_enum)]
+    pub rules: Option<RulesArg>,
+    /// Whether a hit earns the player who made it another turn
+    #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+    pub shoot_again: Option<bool>,
+    /// Whether ships have to be placed apart, not touching even at the corners
+    #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+    pub no_touch: Option<bool>,
+    /// Whether the squares around each sunk ship are marked as water automatically; needs --no-touch
+    #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+    pub reveal_water: Option<bool>,
+    /// Whether each player also gets radar scans, cluster bombs and a torpedo to use in place of a turn's shots
+    #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+    pub advanced: Option<bool>,
+}
+
+#[derive(Debug, Args)]
+pub struct SimulateArgs {
+    /// How many games to play
+    #[arg(long, default_value_t = 1000)]
+    pub games: u32,
+    /// How well player 1 plays
+    #[arg(long, value_enum, default_value_t = Level::Hard)]
+    pub player1: Level,
+    /// How well player 2 plays
+    #[arg(long, value_enum, default_value_t = Level::Hard)]
+    pub player2: Level,
+    /// The fleet each player places
+    #[arg(long, value_parser = parse_fleet, default_value = "standard")]
+    pub fleet: Fleet,
+    /// The board size as WIDTHxHEIGHT
+    #[arg(long, value_parser = parse_size_arg, default_value = "10x10")]
+    pub size: (i32, i32),
+    /// The rules to play by
+    #[arg(long, value_enum, default_value_t = RulesArg::Classic)]
+    pub rules: RulesArg,
+    /// A hit earns the player who made it another turn
+    #[arg(long)]
+    pub shoot_again: bool,
+    /// Ships have to be placed apart, not touching even at the corners
+    #[arg(long)]
+    pub no_touch: bool,
+    /// The squares around each sunk ship are marked as water automatically
+    #[arg(long, requires = "no_touch")]
+    pub reveal_water: bool,
+    /// Each player also gets radar scans, cluster bombs and a torpedo to use in place of a turn's shots
+    #[arg(long)]
+    pub advanced: bool,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Opponent {
+    Human,
+    Computer,
+}
+
+impl From<Opponent> for OpponentChoice {
+    fn from(opponent: Opponent) -> OpponentChoice {
+        match opponent {
+            Opponent::Human => OpponentChoice::Human,
+            Opponent::Computer => OpponentChoice::AI,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Level {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl From<Level> for Difficulty {
+    fn from(level: Level) -> Difficulty {
+        match level {
+            Level::Easy => Difficulty::Easy,
+            Level::Medium => Difficulty::Medium,
+            Level::Hard => Difficulty::Hard,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum NotationArg {
+    Numeric,
+    Lettered,
+}
+
+impl From<NotationArg> for Notation {
+    fn from(notation: NotationArg) -> Notation {
+        match notation {
+            NotationArg::Numeric => Notation::Numeric,
+            NotationArg::Lettered => Notation::Lettered,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum RulesArg {
+    Classic,
+    Salvo,
+}
+
+impl From<RulesArg> for Rules {
+    fn from(rules: RulesArg) -> Rules {
+        Rules { salvo: matches!(rules, RulesArg::Salvo), ..Rules::default() }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum AutoPlace {
+    None,
+    #[value(name = "1")]
+    Player1,
+    #[value(name = "2")]
+    Player2,
+    Both,
+}
+
+impl AutoPlace {
+    pub fn for_player(&self, player: i32) -> Choice {
+        match (self, player) {
+            (AutoPlace::Both, _) | (AutoPlace::Player1, 1) | (AutoPlace::Player2, 2) => Choice::Yes,
+            _ => Choice::No,
+        }
+    }
+}
+
+// Reads a board size such as "10x10" or "20x12", as long as both sides are within the allowed range.
+pub fn parse_size(input: &str) -> Option<(i32, i32)> {
+    let dimensions: Vec<Option<i32>> = input.trim().to_lowercase().split('x').map(|part| part.trim().parse::<i32>().ok()).collect();
+    match dimensions[..] {
+        [Some(width), Some(height)] if (MIN_BOARD_SIZE..=MAX_BOARD_SIZE).contains(&width) && (MIN_BOARD_SIZE..=MAX_BOARD_SIZE).contains(&height) => Some((width, height)),
+        _ => None,
+    }
+}
+
+fn parse_size_arg(input: &str) -> Result<(i32, i32), String> {
+    parse_size(input).ok_or_else(|| format!("expected two numbers between {} and {}, e.g. 8x8 or 20x12", MIN_BOARD_SIZE, MAX_BOARD_SIZE))
+}
+
+fn parse_fleet(input: &str) -> Result<Fleet, String> {
+    match input.trim().to_lowercase().as_str() {
+        "standard" => Ok(Fleet::standard()),
+        "classic" => Ok(Fleet::classic()),
+        "russian" => Ok(Fleet::russian()),
+        _ => parse_custom_fleet(input),
+    }
+}
+
+// Reads a fleet definition such as "Frigate 3 2, Corvette 2 3", where each entry is a name, a length and a count.
+pub fn parse_custom_fleet(input: &str) -> Result<Fleet, String> {
+    let mut entries = Vec::new();
+    for entry in input.trim().split(',') {
+        let parts: Vec<&str> = entry.split_whitespace().collect();
+        let parsed = match parts[..] {
+            [name, length, count] => length.parse::<i32>().ok().zip(count.parse::<i32>().ok()).map(|(length, count)| (name.to_string(), length, count)),
+            _ => None,
+        };
+        match parsed {
+            Some(parsed) => entries.push(parsed),
+            None => return Err(format!("Invalid entry \"{}\", please use NAME LENGTH COUNT (e.g. Frigate 3 2)", entry.trim())),
+        }
+    }
+
+    Fleet::new("Custom", &entries).map_err(|err| err.to_string())
+}
@@ -0,0 +1,138 @@
+use core::fmt;
+
+use rand::Rng;
+use sha2::{Digest, Sha256};
+
+use crate::{game::{Board, PlacementError, Point, Position, ShipType, StrikeOutcome}, weapons::Action};
+
+// A player who answers every strike themselves could lie about what it hit. To keep them honest, each player publishes a
+// hash of their layout, salted so it can't be guessed by hashing candidate layouts, before the first shot is fired. At the
+// end they reveal the layout and the salt, and the other player checks it against the hash and against every answer given.
+
+pub fn generate_salt() -> String {
+    let salt: [u8; 32] = rand::rng().random();
+    to_hex(&salt)
+}
+
+pub fn commit(layout: &[(ShipType, Position)], salt: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    // Serializing a list of plain structs always produces the same bytes, so both sides hash the layout identically.
+    hasher.update(serde_json::to_vec(layout).expect("a layout can always be serialized"));
+    to_hex(&hasher.finalize())
+}
+
+// Why a revealed layout doesn't back up the answers its owner gave during the game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheatError {
+    CommitmentMismatch, // The revealed layout isn't the one committed to at the start
+    InvalidLayout(PlacementError),
+    IncompleteLayout,
+    WrongOutcome { point: Point, reported: StrikeOutcome, actual: StrikeOutcome },
+    WrongScan { centre: Point, reported: bool }, // A radar scan was answered the wrong way round
+}
+
+impl fmt::Display for CheatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheatError::CommitmentMismatch => write!(f, "The revealed layout is not the one committed to at the start of the game"),
+            CheatError::InvalidLayout(err) => write!(f, "The revealed layout is not a legal placement: {}", err),
+            CheatError::IncompleteLayout => write!(f, "The revealed layout is missing ships from the fleet"),
+            CheatError::WrongOutcome { point, reported, actual } => write!(f, "The strike at ({},{}) was reported as {}, but it was actually {}", point.x, point.y, reported, actual),
+            CheatError::WrongScan { centre, reported: true } => write!(f, "The radar scan around ({},{}) was reported to find a ship, but there was none", centre.x, centre.y),
+            CheatError::WrongScan { centre, reported: false } => write!(f, "The radar scan around ({},{}) was reported to find nothing, but there was a ship", centre.x, centre.y),
+        }
+    }
+}
+
+// Checks a revealed layout against the commitment made at the start of the game, then replays every strike made against
+// it, and every radar scan made of it, to confirm that each one was answered truthfully. The layout is placed on the given
+// empty board, which is set up with the game's size, fleet and placement rules, so it has to be legal under those too.
+pub fn verify(commitment: &str, layout: &[(ShipType, Position)], salt: &str, mut board: Board, strikes: &[(Point, StrikeOutcome)], scans: &[(Point, bool)]) -> Result<(), CheatError> {
+    if commit(layout, salt) != commitment {
+        return Err(CheatError::CommitmentMismatch);
+    }
+
+    for (ship_type, pos) in layout {
+        board.place_ship(ship_type.clone(), *pos).map_err(CheatError::InvalidLayout)?;
+    }
+    if board.ships.len() != board.fleet.ship_types().len() {
+        return Err(CheatError::IncompleteLayout);
+    }
+
+    for (centre, reported) in scans {
+        let actual = Action::Radar(*centre).area(&board).iter().any(|point| board.occupied[point.y as usize][point.x as usize]);
+        if actual != *reported {
+            return Err(CheatError::WrongScan { centre: *centre, reported: *reported });
+        }
+    }
+
+    for (point, reported) in strikes {
+        let actual = board.receive_strike(*point);
+        if actual != *reported {
+            return Err(CheatError::WrongOutcome { point: *point, reported: reported.clone(), actual });
+        }
+    }
+    Ok(())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{rules::Rules, testing::{blank_board, point, test_layout}};
+
+    // The answers an honest player would have given to three strikes on the test layout.
+    fn honest_strikes() -> Vec<(Point, StrikeOutcome)> {
+        let submarine = test_layout()[0].0.clone();
+        vec![
+            (point(0, 0), StrikeOutcome::Sunk(submarine)),
+            (point(2, 2), StrikeOutcome::Miss),
+            (point(3, 4), StrikeOutcome::Hit),
+        ]
+    }
+
+    #[test]
+    fn an_honest_game_is_accepted() {
+        let commitment = commit(&test_layout(), "salt");
+        assert_eq!(verify(&commitment, &test_layout(), "salt", blank_board(&Rules::default()), &honest_strikes(), &[]), Ok(()));
+    }
+
+    #[test]
+    fn a_different_salt_or_layout_does_not_match() {
+        let commitment = commit(&test_layout(), "salt");
+        let board = blank_board(&Rules::default());
+        assert_eq!(verify(&commitment, &test_layout(), "pepper", board.clone(), &honest_strikes(), &[]), Err(CheatError::CommitmentMismatch));
+        assert_eq!(verify(&commitment, &test_layout()[..1], "salt", board, &honest_strikes(), &[]), Err(CheatError::CommitmentMismatch));
+    }
+
+    #[test]
+    fn a_layout_missing_a_ship_is_refused() {
+        let partial = &test_layout()[..1];
+        let commitment = commit(partial, "salt");
+        assert_eq!(verify(&commitment, partial, "salt", blank_board(&Rules::default()), &[], &[]), Err(CheatError::IncompleteLayout));
+    }
+
+    #[test]
+    fn a_lie_about_a_strike_is_caught() {
+        let commitment = commit(&test_layout(), "salt");
+        let mut strikes = honest_strikes();
+        strikes[2].1 = StrikeOutcome::Miss;
+        let lie = CheatError::WrongOutcome { point: point(3, 4), reported: StrikeOutcome::Miss, actual: StrikeOutcome::Hit };
+        assert_eq!(verify(&commitment, &test_layout(), "salt", blank_board(&Rules::default()), &strikes, &[]), Err(lie));
+    }
+
+    #[test]
+    fn a_lie_about_a_scan_is_caught() {
+        let commitment = commit(&test_layout(), "salt");
+        let board = blank_board(&Rules::default());
+        let honest_scans = [(point(1, 1), true), (point(2, 2), false)];
+        assert_eq!(verify(&commitment, &test_layout(), "salt", board.clone(), &honest_strikes(), &honest_scans), Ok(()));
+
+        let lie = CheatError::WrongScan { centre: point(1, 1), reported: false };
+        assert_eq!(verify(&commitment, &test_layout(), "salt", board, &honest_strikes(), &[(point(1, 1), false)]), Err(lie));
+    }
+}
@@ -1,8 +1,21 @@
 use core::fmt;
 
+use serde::{Deserialize, Serialize};
 use strum_macros::EnumIter;
 
-pub static BOARD_SIZE: usize = 10;
+pub const DEFAULT_BOARD_SIZE: i32 = 10;
+// Boards must be able to hold the longest ship, and are capped so that both axes keep two-digit labels.
+pub const MIN_BOARD_SIZE: i32 = 5;
+pub const MAX_BOARD_SIZE: i32 = 26;
+
+#[derive(Debug)]
+pub enum StartChoice {
+    New,
+    Resume,
+    Replay,
+    Host,
+    Join
+}
 
 #[derive(Debug)]
 pub enum OpponentChoice {
@@ -10,6 +23,13 @@ pub enum OpponentChoice {
     AI
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard
+}
+
 #[derive(Debug)]
 pub enum Choice {
     Yes,
@@ -0,0 +1,194 @@
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::ShipType;
+
+// A fleet is the list of ship classes each player has to place, along with how many of each. Both players in a game
+// use the same fleet, and it's public knowledge, so the computer is allowed to reason about it too.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fleet {
+    pub name: String,
+    pub classes: Vec<(ShipType, i32)>,
+}
+
+// Why a fleet definition was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FleetError {
+    Empty,
+    InvalidLength(String),
+    InvalidCount(String),
+    DuplicateName(String),
+    InvalidName(String), // Empty, or not made of printable ASCII characters
+    InvalidGlyph(String),
+    DuplicateGlyph(String),
+}
+
+impl fmt::Display for FleetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FleetError::Empty => write!(f, "A fleet needs at least one ship"),
+            FleetError::InvalidLength(name) => write!(f, "The {} needs a length of at least 1", name),
+            FleetError::InvalidCount(name) => write!(f, "There must be at least one {}", name),
+            FleetError::DuplicateName(name) => write!(f, "The {} is listed more than once", name),
+            FleetError::InvalidName(name) => write!(f, "{:?} can't be used as a name, since names are limited to printable ASCII characters", name),
+            FleetError::InvalidGlyph(name) => write!(f, "The {} isn't drawn with a printable ASCII character", name),
+            FleetError::DuplicateGlyph(name) => write!(f, "The {} is drawn with the same character as another class", name),
+        }
+    }
+}
+
+impl Fleet {
+    // Builds a fleet from (name, length, count) entries, picking a distinct glyph for each class to draw it with.
+    pub fn new(name: &str, entries: &[(String, i32, i32)]) -> Result<Fleet, FleetError> {
+        let mut classes: Vec<(ShipType, i32)> = Vec::new();
+        for (class_name, length, count) in entries {
+            let glyph = pick_glyph(class_name, &classes);
+            classes.push((ShipType::new(class_name, *length, glyph), *count));
+        }
+
+        let fleet = Fleet { name: name.to_string(), classes };
+        fleet.validate()?;
+        Ok(fleet)
+    }
+
+    // Checks a fleet against the rules every fleet built by Fleet::new follows, for one that was made elsewhere, such as
+    // one received over the network. Names and glyphs are printed straight to the terminal, so they have to be plain
+    // printable ASCII, and each class needs a glyph of its own to be told apart on the board.
+    pub fn validate(&self) -> Result<(), FleetError> {
+        if !is_printable(&self.name) {
+            return Err(FleetError::InvalidName(self.name.clone()));
+        }
+        if self.classes.is_empty() {
+            return Err(FleetError::Empty);
+        }
+
+        for (index, (ship_type, count)) in self.classes.iter().enumerate() {
+            let earlier = &self.classes[..index];
+            if !is_printable(&ship_type.name) {
+                return Err(FleetError::InvalidName(ship_type.name.clone()));
+            }
+            if ship_type.length < 1 {
+                return Err(FleetError::InvalidLength(ship_type.name.clone()));
+            }
+            if *count < 1 {
+                return Err(FleetError::InvalidCount(ship_type.name.clone()));
+            }
+            if earlier.iter().any(|(other, _)| other.name.eq_ignore_ascii_case(&ship_type.name)) {
+                return Err(FleetError::DuplicateName(ship_type.name.clone()));
+            }
+            if !ship_type.glyph.is_ascii_graphic() {
+                return Err(FleetError::InvalidGlyph(ship_type.name.clone()));
+            }
+            if earlier.iter().any(|(other, _)| other.glyph == ship_type.glyph) {
+                return Err(FleetError::DuplicateGlyph(ship_type.name.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    // One ship of each length from 1 to 5. This is the fleet the game has always used.
+    pub fn standard() -> Fleet {
+        Fleet {
+            name: "Standard".to_string(),
+            classes: vec![
+                (ShipType::new("Submarine", 1, 's'), 1),
+                (ShipType::new("Destroyer", 2, 'd'), 1),
+                (ShipType::new("Cruiser", 3, 'c'), 1),
+                (ShipType::new("Battleship", 4, 'B'), 1),
+                (ShipType::new("Carrier", 5, 'C'), 1),
+            ],
+        }
+    }
+
+    // The Milton Bradley set: 5, 4, 3, 3 and 2.
+    pub fn classic() -> Fleet {
+        Fleet {
+            name: "Classic".to_string(),
+            classes: vec![
+                (ShipType::new("Carrier", 5, 'C'), 1),
+                (ShipType::new("Battleship", 4, 'B'), 1),
+                (ShipType::new("Cruiser", 3, 'c'), 1),
+                (ShipType::new("Submarine", 3, 's'), 1),
+                (ShipType::new("Destroyer", 2, 'd'), 1),
+            ],
+        }
+    }
+
+    // The Russian variant: one 4, two 3s, three 2s and four 1s.
+    pub fn russian() -> Fleet {
+        Fleet {
+            name: "Russian".to_string(),
+            classes: vec![
+                (ShipType::new("Battleship", 4, 'B'), 1),
+                (ShipType::new("Cruiser", 3, 'c'), 2),
+                (ShipType::new("Destroyer", 2, 'd'), 3),
+                (ShipType::new("Submarine", 1, 's'), 4),
+            ],
+        }
+    }
+
+    pub fn presets() -> Vec<Fleet> {
+        vec![Fleet::standard(), Fleet::classic(), Fleet::russian()]
+    }
+
+    // Every individual ship in the fleet, in the order they are placed.
+    pub fn ship_types(&self) -> Vec<ShipType> {
+        let mut ship_types = Vec::new();
+        for (ship_type, count) in &self.classes {
+            for _ in 0..*count {
+                ship_types.push(ship_type.clone());
+            }
+        }
+        ship_types
+    }
+
+    pub fn ship_count(&self) -> usize {
+        self.classes.iter().map(|(_, count)| *count as usize).sum()
+    }
+
+    pub fn longest(&self) -> i32 {
+        self.classes.iter().map(|(ship_type, _)| ship_type.length).max().unwrap_or(0)
+    }
+
+    pub fn total_cells(&self) -> i32 {
+        self.classes.iter().map(|(ship_type, count)| ship_type.length * count).sum()
+    }
+
+    // A fleet fits a board if its longest ship fits along the board and the ships take up no more than half the squares,
+    // which leaves random placement plenty of room. Ships that have to be kept apart each claim the water along one long
+    // and one short side as well, on a board counted one square bigger each way so ships at the edges aren't penalised.
+    pub fn fits(&self, width: i32, height: i32, no_touch: bool) -> bool {
+        if !no_touch {
+            return self.longest() <= width.max(height) && self.total_cells() * 2 <= width * height;
+        }
+        let claimed: i32 = self.classes.iter().map(|(ship_type, count)| (ship_type.length + 1) * 2 * count).sum();
+        self.longest() <= width.max(height) && claimed * 2 <= (width + 1) * (height + 1)
+    }
+}
+
+impl fmt::Display for Fleet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (", self.name)?;
+        for (i, (ship_type, count)) in self.classes.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}x {} [{}]", count, ship_type.name, ship_type.length)?;
+        }
+        write!(f, ")")
+    }
+}
+
+fn is_printable(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_graphic() || c == ' ')
+}
+
+// Prefers the class name's initial in upper then lower case, then its other letters, then digits.
+fn pick_glyph(name: &str, classes: &[(ShipType, i32)]) -> char {
+    let taken = |glyph: &char| classes.iter().any(|(ship_type, _)| ship_type.glyph == *glyph);
+
+    let letters = name.chars().filter(|c| c.is_ascii_alphabetic());
+    let candidates = letters.flat_map(|c| [c.to_ascii_uppercase(), c.to_ascii_lowercase()]);
+    candidates.chain('0'..='9').find(|glyph| !taken(glyph)).unwrap_or('#')
+}
@@ -1,24 +1,112 @@
 use core::fmt;
-use std::{collections::{HashSet}};
-use strum_macros::{EnumIter};
-use strum::IntoEnumIterator;
+use std::{collections::{HashSet}, iter::Flatten, slice};
 
-use crate::{enums::{BOARD_SIZE}, helpers::{check_position_valid, input_ship_positon}};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+use crate::{enums::MAX_BOARD_SIZE, fleet::Fleet, helpers::{check_position_valid, generate_random_position, GameRng}, notation::Notation, rules::Rules, weapons::{resolve, Action, ActionResult, Arsenal, Weapon}};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Point {
     pub x: i32,
     pub y: i32,
 }
 
-#[derive(Debug, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Impact {
-    coords: Point,
-    hit: bool
+    pub coords: Point,
+    pub hit: bool,
+    pub sunk: Option<ShipType>, // The ship this strike finished off, if any
+    #[serde(default)] // Version 8 saves predate revealed water
+    pub revealed: bool, // Marked as water next to a sunk ship rather than fired at
+}
+
+// Every impact on a board, kept on a grid so that looking up a square doesn't mean searching them all. The computer
+// looks squares up many times for every shot it takes. Each square holds at most one impact, and impacts are saved as
+// a plain list. The grid never grows past the largest board allowed; keeping impacts on the board itself is up to Board.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Impacts {
+    grid: Vec<Vec<Option<Impact>>>, // Indexed as [y][x], and grown as impacts are added
+    count: usize,
+}
+
+impl Impacts {
+    pub fn get(&self, point: &Point) -> Option<&Impact> {
+        if point.x < 0 || point.y < 0 {
+            return None;
+        }
+        self.grid.get(point.y as usize)?.get(point.x as usize)?.as_ref()
+    }
+
+    pub fn contains(&self, point: &Point) -> bool {
+        self.get(point).is_some()
+    }
+
+    // Adds an impact, replacing any already recorded at the same square. Returns false, adding nothing, if the square
+    // couldn't be on any board.
+    pub fn insert(&mut self, impact: Impact) -> bool {
+        let sizes = 0..MAX_BOARD_SIZE;
+        if !sizes.contains(&impact.coords.x) || !sizes.contains(&impact.coords.y) {
+            return false;
+        }
+        let (x, y) = (impact.coords.x as usize, impact.coords.y as usize);
+        if self.grid.len() <= y {
+            self.grid.resize(y + 1, Vec::new());
+        }
+        let row = &mut self.grid[y];
+        if row.len() <= x {
+            row.resize(x + 1, None);
+        }
+        if row[x].replace(impact).is_none() {
+            self.count += 1;
+        }
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    // Every impact, in row order.
+    pub fn iter(&self) -> ImpactsIter<'_> {
+        self.grid.iter().flatten().flatten()
+    }
+}
+
+pub type ImpactsIter<'a> = Flatten<Flatten<slice::Iter<'a, Vec<Option<Impact>>>>>;
+
+impl<'a> IntoIterator for &'a Impacts {
+    type Item = &'a Impact;
+    type IntoIter = ImpactsIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl Serialize for Impacts {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de> Deserialize<'de> for Impacts {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut impacts = Impacts::default();
+        for impact in Vec::<Impact>::deserialize(deserializer)? {
+            if !impacts.insert(impact) {
+                return Err(serde::de::Error::custom("an impact lies off the board"));
+            }
+        }
+        Ok(impacts)
+    }
 }
 
 // Position enum enforces constraint that ships must be placed horizontally or vertically, not diagonally.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Position {
     Horizontal { start_x: i32, end_x: i32, y: i32 },
     Vertical { start_y: i32, end_y: i32, x: i32 },
@@ -41,6 +129,21 @@ impl Position {
         }
         coords
     }
+
+    // How far along the position the point lies, counting from its start, or None if the position doesn't cover it.
+    pub fn offset_of(&self, point: &Point) -> Option<usize> {
+        match *self {
+            Position::Horizontal { start_x, end_x, y } if point.y == y && (start_x..=end_x).contains(&point.x) => Some((point.x - start_x) as usize),
+            Position::Vertical { start_y, end_y, x } if point.x == x && (start_y..=end_y).contains(&point.y) => Some((point.y - start_y) as usize),
+            _ => None,
+        }
+    }
+
+    // Whether any square of this position is next to, or on, a square of the other, counting diagonal neighbours.
+    pub fn touches(&self, other: &Position) -> bool {
+        let others = other.coordinates();
+        self.coordinates().iter().any(|coord| others.iter().any(|other| (coord.x - other.x).abs() <= 1 && (coord.y - other.y).abs() <= 1))
+    }
 }
 
 impl Default for Position {
@@ -49,7 +152,7 @@ impl Default for Position {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Ship {
     pub health: Vec<bool>,
     pub pos: Position,
@@ -59,223 +162,678 @@ pub struct Ship {
 impl Ship {
     // Returns true if the ship has no health left
     pub fn sunk(&self) -> bool {
-        return self.health.iter().all(|&h| h == false);
+        self.health.iter().all(|&h| !h)
     }
 
-    // Registers a hit on the ship at the given impact point, if it hits. Returns true if hit, false otherwise.
-    pub fn hit(&mut self, impact_point: &Point) -> bool {
-        let coords = self.pos.coordinates();
-        for (i, coord) in coords.iter().enumerate() {
-            if coord.x == impact_point.x && coord.y == impact_point.y {
-                self.health[i] = false;
-                return true;
-            }
+    // Registers a hit on the ship at the given impact point, if it hits. Reports Sunk only for the strike that takes out
+    // the ship's last healthy square, so a sinking is announced exactly once.
+    pub fn hit(&mut self, impact_point: &Point) -> HitResult {
+        let Some(i) = self.pos.offset_of(impact_point) else {
+            return HitResult::Miss;
+        };
+        let was_afloat = !self.sunk();
+        self.health[i] = false;
+        if was_afloat && self.sunk() {
+            return HitResult::Sunk;
         }
-        return false;
+        HitResult::Hit
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, EnumIter)]
-pub enum ShipType {
-    Submarine { health: [bool; 1], pos: Position },
-    Destroyer { health: [bool; 2], pos: Position },
-    Cruiser { health: [bool; 3], pos: Position },
-    Battleship { health: [bool; 4], pos: Position },
-    Carrier { health: [bool; 5], pos: Position },
+// The effect of a strike on a single ship.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HitResult {
+    Miss,
+    Hit,
+    Sunk,
+}
+
+// A class of ship, as described by the fleet in play. The glyph is what the ship is drawn with on its owner's grid.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ShipType {
+    pub name: String,
+    pub length: i32,
+    pub glyph: char,
+}
+
+impl ShipType {
+    pub fn new(name: &str, length: i32, glyph: char) -> ShipType {
+        ShipType { name: name.to_string(), length, glyph }
+    }
 }
 
 impl fmt::Display for ShipType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+// The result of firing at a point, as seen by the player who fired.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StrikeOutcome {
+    Miss,
+    Hit,
+    Sunk(ShipType),
+    AlreadyStruck,
+    GameOver(ShipType), // The strike sank the given ship, which was the last one afloat
+}
+
+impl StrikeOutcome {
+    // The ship this strike sent to the bottom, if any.
+    pub fn sunk_ship(&self) -> Option<ShipType> {
         match self {
-            ShipType::Submarine { .. } => write!(f, "Submarine"),
-            ShipType::Destroyer { .. } => write!(f, "Destroyer"),
-            ShipType::Cruiser { .. } => write!(f, "Cruiser"),
-            ShipType::Battleship { .. } => write!(f, "Battleship"),
-            ShipType::Carrier { .. } => write!(f, "Carrier"),
+            StrikeOutcome::Sunk(ship_type) | StrikeOutcome::GameOver(ship_type) => Some(ship_type.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn is_hit(&self) -> bool {
+        matches!(self, StrikeOutcome::Hit | StrikeOutcome::Sunk(_) | StrikeOutcome::GameOver(_))
+    }
+}
+
+impl fmt::Display for StrikeOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StrikeOutcome::Miss => write!(f, "a miss"),
+            StrikeOutcome::Hit => write!(f, "a hit"),
+            StrikeOutcome::Sunk(ship_type) => write!(f, "a hit sinking the {}", ship_type),
+            StrikeOutcome::GameOver(ship_type) => write!(f, "a hit sinking the {} and ending the game", ship_type),
+            StrikeOutcome::AlreadyStruck => write!(f, "a repeat strike"),
         }
     }
 }
 
+// Why a ship could not be placed at the requested position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlacementError {
+    OutOfBounds,
+    Overlap,
+    Touching(ShipType), // The ship would lie next to the given one, which no-touch rules forbid
+    WrongLength,
+    NotInFleet, // The fleet has no ship of this type left to place
+}
+
+impl fmt::Display for PlacementError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlacementError::OutOfBounds => write!(f, "Ship does not fit on the board"),
+            PlacementError::Overlap => write!(f, "Ship overlaps another"),
+            PlacementError::Touching(ship_type) => write!(f, "Ship would touch the {}", ship_type),
+            PlacementError::WrongLength => write!(f, "Position does not match the ship's length"),
+            PlacementError::NotInFleet => write!(f, "No more ships of this type are left to place"),
+        }
+    }
+}
 
 // In Battleship, ships are placed on a board made up of a grid. Instead of implementing this as just a 2D array with ships
 // "filling" grid squares, maintain a mapping of ship to position ranges, given ships are 1xn rectangles, where n is the
 // length and thus "HP" of a ship. This makes it easier to track which square "makes up" which ship.
 // Each player has a board struct for their side.
-#[derive(Debug)]
+// Both players' boards in a game share the same dimensions, since each board's impacts are recorded against the other.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Board {
-    pub occupied: [[bool; BOARD_SIZE]; BOARD_SIZE], // 2D array to track occupied squares
+    pub width: i32,
+    pub height: i32,
+    pub fleet: Fleet,
+    pub occupied: Vec<Vec<bool>>, // 2D grid to track occupied squares, indexed as [y][x]
     pub ships: Vec<Ship>,
-    pub impacts: HashSet<Impact>
+    pub impacts: Impacts,
+    pub received: HashSet<Point>, // Every square the opponent has fired at on this board
+    pub last_impact: Option<Point>, // The most recent strike this board's owner made
+    pub last_received: Option<Point>, // The most recent strike the opponent made on this board
+    pub notation: Notation, // How squares are named to this board's owner
+    pub no_touch: bool, // Ships on this board may not be placed next to each other, not even diagonally
+    pub reveal_water: bool, // The squares around each ship this board's owner sinks are marked as water for them
+    pub contacts: Vec<Point>, // The centre of every radar scan by this board's owner that found a ship
+    // The opponent's answers can't be trusted until their layout is revealed at the end, as in networked games, so water
+    // revealed by those answers is only a hint and can still be fired at. Networked games are never saved.
+    #[serde(skip)]
+    pub unverified_reveals: bool,
 }
 
 impl Board {
+    pub fn new(width: i32, height: i32, fleet: Fleet) -> Board {
+        Board { width, height, fleet, occupied: vec![vec![false; width as usize]; height as usize], ships: Vec::new(), impacts: Impacts::default(), received: HashSet::new(), last_impact: None, last_received: None, notation: Notation::default(), no_touch: false, reveal_water: false, contacts: Vec::new(), unverified_reveals: false }
+    }
+
+    // An empty board for a game played under the given rules, with its squares named in the given notation. The placement
+    // and reveal rules are the only ones a board needs to know about itself.
+    pub fn with_rules(width: i32, height: i32, fleet: Fleet, rules: &Rules, notation: Notation) -> Board {
+        Board { notation, no_touch: rules.no_touch, reveal_water: rules.reveal_water, ..Board::new(width, height, fleet) }
+    }
+
+    pub fn in_bounds(&self, point: &Point) -> bool {
+        point.x >= 0 && point.x < self.width && point.y >= 0 && point.y < self.height
+    }
+
+    // Places a ship of the given type, checking that it fits on the board and doesn't overlap any ship already placed, nor
+    // touch one if ships have to be kept apart.
+    pub fn place_ship(&mut self, ship_type: ShipType, pos: Position) -> Result<(), PlacementError> {
+        let allowed = self.fleet.ship_types().iter().filter(|fleet_type| **fleet_type == ship_type).count();
+        let placed = self.ships.iter().filter(|ship| ship.ship_type == ship_type).count();
+        if placed >= allowed {
+            return Err(PlacementError::NotInFleet);
+        }
+
+        let coords = pos.coordinates();
+        if coords.len() as i32 != ship_type.length {
+            return Err(PlacementError::WrongLength);
+        }
+        if coords.iter().any(|coord| !self.in_bounds(coord)) {
+            return Err(PlacementError::OutOfBounds);
+        }
+        if !check_position_valid(&pos, &self.occupied) {
+            return Err(PlacementError::Overlap);
+        }
+        if self.no_touch && let Some(ship) = self.ships.iter().find(|ship| ship.pos.touches(&pos)) {
+            return Err(PlacementError::Touching(ship.ship_type.clone()));
+        }
+
+        for coord in &coords {
+            self.occupied[coord.y as usize][coord.x as usize] = true;
+        }
+        self.ships.push(Ship { health: vec![true; coords.len()], pos, ship_type });
+        Ok(())
+    }
+
+    // Places every ship in the fleet at random. Nothing is reported, so the layout can be kept secret (e.g. for a computer player).
+    // On a small board earlier ships can leave no room for later ones, in which case the whole layout is started over.
+    pub fn place_randomly(&mut self, rng: &mut GameRng) {
+        'layout: loop {
+            self.ships.clear();
+            self.occupied = vec![vec![false; self.width as usize]; self.height as usize];
+
+            for ship_type in self.fleet.ship_types() {
+                let Some(position) = generate_random_position(ship_type.length, &mut self.occupied, self.no_touch, rng) else {
+                    continue 'layout;
+                };
+                self.ships.push(Ship { health: vec![true; ship_type.length as usize], pos: position, ship_type });
+            }
+            return;
+        }
+    }
+
+    // An empty board set up the same way as this one, with no ships placed and no strikes made.
+    pub fn blank(&self) -> Board {
+        Board { notation: self.notation, no_touch: self.no_touch, reveal_water: self.reveal_water, unverified_reveals: self.unverified_reveals, ..Board::new(self.width, self.height, self.fleet.clone()) }
+    }
+
+    // Where each ship was placed, without any record of the damage it has taken since.
+    pub fn layout(&self) -> Vec<(ShipType, Position)> {
+        self.ships.iter().map(|ship| (ship.ship_type.clone(), ship.pos)).collect()
+    }
+
+    // Resolves an opponent's strike against this board's ships, returning the outcome as the attacker should hear it.
+    pub fn receive_strike(&mut self, point: Point) -> StrikeOutcome {
+        self.received.insert(point);
+        self.last_received = Some(point);
+        let mut hit = false;
+        let mut sunk = None;
+        for ship in self.ships.iter_mut() {
+            match ship.hit(&point) {
+                HitResult::Miss => continue,
+                HitResult::Hit => hit = true,
+                HitResult::Sunk => {
+                    hit = true;
+                    sunk = Some(ship.ship_type.clone());
+                }
+            }
+            break;
+        }
+
+        match sunk {
+            Some(ship_type) if self.fleet_sunk() => StrikeOutcome::GameOver(ship_type),
+            Some(ship_type) => StrikeOutcome::Sunk(ship_type),
+            None if hit => StrikeOutcome::Hit,
+            None => StrikeOutcome::Miss,
+        }
+    }
+
+    // Records the outcome of a strike this board's owner made against the opponent. The square may already have been
+    // revealed as water by an earlier shot of the same turn, e.g. one that sank a ship next to it, in which case the
+    // strike takes the revealed mark's place. A strike off the board isn't recorded.
+    pub fn record_strike(&mut self, point: Point, outcome: &StrikeOutcome) {
+        if !self.in_bounds(&point) {
+            return;
+        }
+        let hit = !matches!(outcome, StrikeOutcome::Miss | StrikeOutcome::AlreadyStruck);
+        self.impacts.insert(Impact { coords: point, hit, sunk: outcome.sunk_ship(), revealed: false });
+        self.last_impact = Some(point);
+        if self.reveal_water && outcome.sunk_ship().is_some() {
+            self.reveal_water_around(point);
+        }
+    }
+
+    // Records what one of this board owner's special weapons found. A radar scan that finds nothing shows the whole area
+    // to be water, so those squares are marked just as revealed water is.
+    pub fn record_action(&mut self, action: &Action, result: &ActionResult) {
+        match (action, result) {
+            (_, ActionResult::Strikes(strikes)) => {
+                for (point, outcome) in strikes {
+                    self.record_strike(*point, outcome);
+                }
+            },
+            (Action::Radar(centre), ActionResult::Scan(true)) => self.contacts.push(*centre),
+            (_, ActionResult::Scan(_)) => {
+                for point in action.area(self) {
+                    if !self.struck(&point) {
+                        self.impacts.insert(Impact { coords: point, hit: false, sunk: None, revealed: true });
+                    }
+                }
+            },
+        }
+    }
+
+    // Marks every square around the wreck of the ship just sunk at the given point as water. This relies on ships not
+    // touching: the wreck is then exactly the hits joined to that point, and nothing else can lie next to it.
+    fn reveal_water_around(&mut self, point: Point) {
+        let hits: HashSet<Point> = self.impacts.iter().filter(|impact| impact.hit).map(|impact| impact.coords).collect();
+        let mut wreck = vec![point];
+        let mut next = 0;
+        while let Some(square) = wreck.get(next).copied() {
+            for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+                let neighbour = Point { x: square.x + dx, y: square.y + dy };
+                if hits.contains(&neighbour) && !wreck.contains(&neighbour) {
+                    wreck.push(neighbour);
+                }
+            }
+            next += 1;
+        }
+
+        for square in wreck {
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    let neighbour = Point { x: square.x + dx, y: square.y + dy };
+                    if self.in_bounds(&neighbour) && !self.struck(&neighbour) {
+                        self.impacts.insert(Impact { coords: neighbour, hit: false, sunk: None, revealed: true });
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn fleet_placed(&self) -> bool {
+        self.ships.len() == self.fleet.ship_count()
+    }
+
+    // Returns true once every ship the fleet calls for has been placed and sunk.
+    pub fn fleet_sunk(&self) -> bool {
+        self.fleet_placed() && self.ships.iter().all(|ship| ship.sunk())
+    }
+
+    pub fn ships_afloat(&self) -> usize {
+        self.ships.iter().filter(|ship| !ship.sunk()).count()
+    }
+
+    // How many squares this board's owner has yet to fire at.
+    pub fn open_squares(&self) -> usize {
+        let struck = if self.unverified_reveals { self.impacts.iter().filter(|impact| !impact.revealed).count() } else { self.impacts.len() };
+        ((self.width * self.height) as usize).saturating_sub(struck)
+    }
+
+    // Returns true if this board's owner has already fired at the given point, or knows it to be water
+    pub fn struck(&self, point: &Point) -> bool {
+        self.impacts.get(point).is_some_and(|impact| !(impact.revealed && self.unverified_reveals))
+    }
+
     // Returns stats about hits made by a particular player (total hits, total attacks launcehd)
     pub fn hit_stats(&self) -> (i32, i32) {
         let mut hit_count = 0;
-        for impact in &self.impacts {
+        let mut strike_count = 0;
+        for impact in self.impacts.iter().filter(|impact| !impact.revealed) {
             if impact.hit {
                 hit_count += 1;
             }
+            strike_count += 1;
         }
-        return (hit_count, self.impacts.len() as i32);
+        (hit_count, strike_count)
     }
 }
 
-#[derive(Debug)]
+// Where a game is up to. Both players place their ships, then take turns firing until one of them sinks the other's
+// whole fleet, at which point the game is over and no more strikes are accepted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Phase {
+    Placement,
+    PlayerTurn(i32),
+    GameOver(i32), // The player who won
+}
+
+// Why a strike was refused without being fired.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TurnError {
+    NotStarted,
+    NotYourTurn,
+    GameOver,
+    WrongShotCount { expected: usize, fired: usize },
+    RepeatedTarget(Point), // Already fired at, or picked twice in the same salvo
+    NoneLeft(Weapon), // The player has none of this weapon left, or never had any outside advanced mode
+    OffBoard, // A shot or special weapon aimed somewhere off the board
+}
+
+impl fmt::Display for TurnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TurnError::NotStarted => write!(f, "The ships have not all been placed yet"),
+            TurnError::NotYourTurn => write!(f, "It is not this player's turn"),
+            TurnError::GameOver => write!(f, "The game is already over"),
+            TurnError::WrongShotCount { expected, fired } => write!(f, "This turn takes {} shots, but {} were fired", expected, fired),
+            TurnError::RepeatedTarget(point) => write!(f, "The square at ({},{}) is targeted more than once", point.x, point.y),
+            TurnError::NoneLeft(weapon) => write!(f, "There is no {} left to use", weapon),
+            TurnError::OffBoard => write!(f, "The target is not on the board"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameState {
     pub player1_board: Board,
-    pub player2_board: Board
+    pub player2_board: Board,
+    pub phase: Phase,
+    pub rules: Rules,
+    pub arsenals: [Arsenal; 2], // The special weapons each player has left, indexed by player number minus one
 }
 
 impl GameState {
-    pub fn all_ships_sunk(&self, player: i32) -> bool {
-        let board = if player == 1 { &self.player1_board } else { &self.player2_board };
-        let mut res = true;
-        for ship in &board.ships {
-            res = res && ship.sunk();
+    pub fn new(player1_board: Board, player2_board: Board) -> GameState {
+        GameState { player1_board, player2_board, phase: Phase::Placement, rules: Rules::default(), arsenals: Default::default() }
+    }
+
+    // Ends the placement phase and hands the first turn to the given player, once both fleets are fully placed.
+    pub fn begin(&mut self, first_player: i32) -> bool {
+        if self.phase != Phase::Placement || !self.player1_board.fleet_placed() || !self.player2_board.fleet_placed() {
+            return false;
+        }
+        self.phase = Phase::PlayerTurn(first_player);
+        self.arsenals = [Arsenal::for_rules(&self.rules); 2];
+        true
+    }
+
+    // The player who fires next, while the game is being played.
+    pub fn current_player(&self) -> Option<i32> {
+        match self.phase {
+            Phase::PlayerTurn(player) => Some(player),
+            _ => None,
         }
-        return res;
+    }
+
+    pub fn winner(&self) -> Option<i32> {
+        match self.phase {
+            Phase::GameOver(player) => Some(player),
+            _ => None,
+        }
+    }
+
+    pub fn board(&self, player: i32) -> &Board {
+        if player == 1 { &self.player1_board } else { &self.player2_board }
+    }
+
+    pub fn all_ships_sunk(&self, player: i32) -> bool {
+        self.board(player).fleet_sunk()
     }
 
     pub fn already_struck(&self, player: i32, strike_coords: Point) -> bool {
-        let board = if player == 1 { &self.player1_board } else { &self.player2_board };
-        return board.impacts.iter().any(|impact| impact.coords.x == strike_coords.x && impact.coords.y == strike_coords.y);
+        self.board(player).struck(&strike_coords)
     }
 
-    pub fn register_strike(&mut self, player: i32, strike_coords: Point) -> bool {
-        let (board, opponent_board) = if player == 1 {
-            (&mut self.player1_board, &mut self.player2_board)
-        } else {
-            (&mut self.player2_board, &mut self.player1_board)
-        };
+    // How many shots the player fires on their turn: always one under classic rules, or one for each of their ships
+    // still afloat under salvo rules.
+    pub fn shots_this_turn(&self, player: i32) -> usize {
+        let board = self.board(player);
+        self.rules.shots(board.ships_afloat(), board.open_squares())
+    }
 
-        let mut hit = false;
-        for ship in opponent_board.ships.iter_mut() {
-            if ship.hit(&strike_coords) {
-                hit = true;
-                println!("Hit!");
-                break;
-            }
+    // Fires at the given point on the opponent's board on behalf of the player whose turn it is, recording the impact on
+    // the player's board and moving the game on: to the other player's turn (or the same player's again, if the rules reward
+    // a hit), or straight to the end on the winning strike.
+    // A point that was already struck costs nothing, so the same player goes again, but one off the board is refused. Turns that take more than one shot
+    // have to go through register_salvo instead.
+    pub fn register_strike(&mut self, player: i32, strike_coords: Point) -> Result<StrikeOutcome, TurnError> {
+        self.check_turn(player)?;
+        let expected = self.shots_this_turn(player);
+        if expected != 1 {
+            return Err(TurnError::WrongShotCount { expected, fired: 1 });
         }
-        if !hit {
-            println!("Miss!");
+        if !self.board(player).in_bounds(&strike_coords) {
+            return Err(TurnError::OffBoard);
+        }
+        if self.already_struck(player, strike_coords) {
+            return Ok(StrikeOutcome::AlreadyStruck);
         }
-        board.impacts.insert(Impact { coords: strike_coords, hit });
-        return true;
-    }
 
-    pub fn draw_board(&self, player: i32) {
-        let board = if player == 1 { &self.player1_board } else { &self.player2_board };
+        let outcome = self.fire(player, strike_coords);
+        self.end_turn(player, std::slice::from_ref(&outcome));
+        Ok(outcome)
+    }
 
-        let mut impact_board: [[char; 10]; 10] = [['.'; BOARD_SIZE]; BOARD_SIZE];
-        for impact in &board.impacts {
-            if impact.hit {
-                impact_board[impact.coords.y as usize][impact.coords.x as usize] = 'X';
-            } else {
-                impact_board[impact.coords.y as usize][impact.coords.x as usize] = '.';
+    // Fires every shot of the player's turn at once. There must be exactly as many as shots_this_turn allows, and none of
+    // them may be off the board, at a square fired at before or picked twice; otherwise nothing is fired at all. The
+    // outcomes come back in the same order as the points.
+    pub fn register_salvo(&mut self, player: i32, points: &[Point]) -> Result<Vec<StrikeOutcome>, TurnError> {
+        self.check_turn(player)?;
+        let expected = self.shots_this_turn(player);
+        if points.len() != expected {
+            return Err(TurnError::WrongShotCount { expected, fired: points.len() });
+        }
+        for (index, point) in points.iter().enumerate() {
+            if !self.board(player).in_bounds(point) {
+                return Err(TurnError::OffBoard);
+            }
+            if self.already_struck(player, *point) || points[..index].contains(point) {
+                return Err(TurnError::RepeatedTarget(*point));
             }
         }
 
-        let mut ship_board: [[char; 10]; 10] = [['.'; BOARD_SIZE]; BOARD_SIZE];
-        for ship in &board.ships {
-            for (coord, index) in ship.pos.coordinates().iter().zip(0..) {
-                let display_unit;
-                if !ship.health[index] {
-                    display_unit = '†';
-                } else {
-                    display_unit = match ship.ship_type {
-                        ShipType::Submarine { .. } => 's',
-                        ShipType::Destroyer { .. } => 'd',
-                        ShipType::Cruiser { .. } => 'c',
-                        ShipType::Battleship { .. } => 'B',
-                        ShipType::Carrier { .. } => 'C',
-                    };
-                }
-                ship_board[coord.y as usize][coord.x as usize] = display_unit;
-            }
+        let outcomes: Vec<StrikeOutcome> = points.iter().map(|point| self.fire(player, *point)).collect();
+        self.end_turn(player, &outcomes);
+        Ok(outcomes)
+    }
+
+    // Uses one of the player's special weapons as their whole turn. Every square it strikes is recorded on the player's board
+    // just like a shot; squares they have already struck are passed over.
+    pub fn register_action(&mut self, player: i32, action: Action) -> Result<ActionResult, TurnError> {
+        self.check_turn(player)?;
+        if !action.in_bounds(self.board(player)) {
+            return Err(TurnError::OffBoard);
+        }
+        if !self.arsenals[(player - 1) as usize].take(action.weapon()) {
+            return Err(TurnError::NoneLeft(action.weapon()));
         }
 
-        println!("Impacts");
-        println!("  0 1 2 3 4 5 6 7 8 9");
-        for y in 0..BOARD_SIZE {
-            print!("{} ", y);
-            for x in 0..BOARD_SIZE {
-                print!("{} ", impact_board[y][x]);
-            }
-            println!();
-        }
-        println!("---------------------");
-        println!("Your ships");
-        println!("  0 1 2 3 4 5 6 7 8 9");
-        for y in 0..BOARD_SIZE {
-            print!("{} ", y);
-            for x in 0..BOARD_SIZE {
-                print!("{} ", ship_board[y][x]);
-            }
-            println!();
+        let (board, opponent_board) = self.boards_mut(player);
+        let result = resolve(&action, opponent_board, board);
+        board.record_action(&action, &result);
+        self.end_turn(player, &result.outcomes());
+        Ok(result)
+    }
+
+    fn check_turn(&self, player: i32) -> Result<(), TurnError> {
+        match self.phase {
+            Phase::Placement => Err(TurnError::NotStarted),
+            Phase::GameOver(_) => Err(TurnError::GameOver),
+            Phase::PlayerTurn(current) if current != player => Err(TurnError::NotYourTurn),
+            Phase::PlayerTurn(_) => Ok(()),
         }
     }
-}
 
-pub fn auto_place_ships(player_placements: &mut Vec<Ship>) -> [[bool; BOARD_SIZE]; BOARD_SIZE] {
-    println!("Automatically placing ships...\n=============================");
-
-    let mut occupied = [[false; BOARD_SIZE]; BOARD_SIZE];
-    for ship_type in ShipType::iter() {
-        let ship_length = match ship_type {
-            ShipType::Submarine { .. } => 1,
-            ShipType::Destroyer { .. } => 2,
-            ShipType::Cruiser { .. } => 3,
-            ShipType::Battleship { .. } => 4,
-            ShipType::Carrier { .. } => 5,
-        };
-        let position = crate::helpers::generate_random_position(ship_length, &mut occupied);
-        let ship = Ship { health: vec![true; ship_length as usize], pos: position, ship_type: ship_type };
-        println!("{:?} placed at {:?}", ship_type, position);
-        player_placements.push(ship);
+    // Resolves a single shot against the opponent's board and records it on the player's own.
+    fn fire(&mut self, player: i32, strike_coords: Point) -> StrikeOutcome {
+        let (board, opponent_board) = self.boards_mut(player);
+        let outcome = opponent_board.receive_strike(strike_coords);
+        board.record_strike(strike_coords, &outcome);
+        outcome
     }
 
-    return occupied;
-}
+    // The player's own board, then their opponent's.
+    fn boards_mut(&mut self, player: i32) -> (&mut Board, &mut Board) {
+        if player == 1 {
+            (&mut self.player1_board, &mut self.player2_board)
+        } else {
+            (&mut self.player2_board, &mut self.player1_board)
+        }
+    }
 
-pub fn place_ships(player: i32, player_placements: &mut Vec<Ship>) -> [[bool; BOARD_SIZE]; BOARD_SIZE] {
-    println!("Player {:?}, place your ships.\n=============================", player);
-
-    let mut occupied = [[false; BOARD_SIZE]; BOARD_SIZE];
-    for ship_type in ShipType::iter() {
-        let ship_length = match ship_type {
-            ShipType::Submarine { .. } => 1,
-            ShipType::Destroyer { .. } => 2,
-            ShipType::Cruiser { .. } => 3,
-            ShipType::Battleship { .. } => 4,
-            ShipType::Carrier { .. } => 5,
+    fn end_turn(&mut self, player: i32, outcomes: &[StrikeOutcome]) {
+        self.phase = if outcomes.iter().any(|outcome| matches!(outcome, StrikeOutcome::GameOver(_))) {
+            Phase::GameOver(player)
+        } else if self.rules.fires_again(outcomes) {
+            Phase::PlayerTurn(player)
+        } else {
+            Phase::PlayerTurn(3 - player)
         };
+    }
+}
 
-        let mut position;
-        loop {
-            println!("Place your {} (length {})", ship_type, ship_length);
-            position = input_ship_positon(ship_length);
-            if check_position_valid(&position, &occupied) {
-                break;
-            }
-            println!("Ship overlaps another, please choose another position.");
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{blank_board, placed_board, point, test_game};
+
+    #[test]
+    fn the_game_begins_once_both_fleets_are_placed() {
+        let rules = Rules::default();
+        let mut game = GameState::new(placed_board(&rules), blank_board(&rules));
+        assert!(!game.begin(1));
+        assert_eq!(game.current_player(), None);
+
+        game.player2_board = placed_board(&rules);
+        assert!(game.begin(2));
+        assert_eq!(game.current_player(), Some(2));
+        assert!(!game.already_struck(2, point(0, 0)));
+        assert!(!game.begin(1));
+    }
+
+    #[test]
+    fn impacts_off_any_board_are_refused() {
+        let mut impacts = Impacts::default();
+        for coords in [point(-1, 0), point(0, -1), point(MAX_BOARD_SIZE, 0), point(0, i32::MAX)] {
+            assert!(!impacts.insert(Impact { coords, hit: false, sunk: None, revealed: false }));
         }
+        assert!(impacts.is_empty());
+        assert!(serde_json::from_str::<Impacts>(r#"[{"coords":{"x":2000000000,"y":0},"hit":false,"sunk":null}]"#).is_err());
+
+        let mut board = blank_board(&Rules::default());
+        board.record_strike(point(5, 0), &StrikeOutcome::Miss);
+        assert!(board.impacts.is_empty());
+        board.record_strike(point(4, 0), &StrikeOutcome::Miss);
+        assert!(board.struck(&point(4, 0)));
+        let reloaded: Impacts = serde_json::from_str(&serde_json::to_string(&board.impacts).unwrap()).unwrap();
+        assert_eq!(reloaded, board.impacts);
+    }
+
+    #[test]
+    fn strikes_are_refused_until_the_game_begins() {
+        let rules = Rules::default();
+        let mut game = GameState::new(placed_board(&rules), placed_board(&rules));
+        assert_eq!(game.register_strike(1, point(2, 2)), Err(TurnError::NotStarted));
+    }
 
-        for coord in position.coordinates() {
-            occupied[coord.y as usize][coord.x as usize] = true;
+    #[test]
+    fn a_miss_passes_the_turn() {
+        let mut game = test_game(Rules::default());
+        assert_eq!(game.register_strike(1, point(2, 2)), Ok(StrikeOutcome::Miss));
+        assert_eq!(game.current_player(), Some(2));
+        assert_eq!(game.register_strike(1, point(2, 3)), Err(TurnError::NotYourTurn));
+    }
+
+    #[test]
+    fn striking_a_square_again_keeps_the_turn() {
+        let mut game = test_game(Rules::default());
+        game.register_strike(1, point(2, 2)).unwrap();
+        game.register_strike(2, point(2, 2)).unwrap();
+        assert_eq!(game.register_strike(1, point(2, 2)), Ok(StrikeOutcome::AlreadyStruck));
+        assert_eq!(game.current_player(), Some(1));
+    }
+
+    #[test]
+    fn strikes_off_the_board_are_refused() {
+        let mut game = test_game(Rules::default());
+        for target in [point(-1, 0), point(5, 3), point(0, 50)] {
+            assert_eq!(game.register_strike(1, target), Err(TurnError::OffBoard));
         }
+        assert_eq!(game.current_player(), Some(1));
+        assert_eq!(game.board(1).open_squares(), 25);
+    }
 
-        let ship = Ship { health: vec![true; ship_length as usize], pos: position, ship_type: ship_type };
-        player_placements.push(ship);
-        println!();
+    #[test]
+    fn the_winning_strike_ends_the_game() {
+        let mut game = test_game(Rules::default());
+        let submarine = game.player2_board.ships[0].ship_type.clone();
+        let destroyer = game.player2_board.ships[1].ship_type.clone();
+        assert_eq!(game.register_strike(1, point(0, 0)), Ok(StrikeOutcome::Sunk(submarine)));
+        game.register_strike(2, point(2, 2)).unwrap();
+        assert_eq!(game.register_strike(1, point(3, 4)), Ok(StrikeOutcome::Hit));
+        game.register_strike(2, point(2, 3)).unwrap();
+        assert_eq!(game.register_strike(1, point(4, 4)), Ok(StrikeOutcome::GameOver(destroyer)));
+        assert_eq!(game.winner(), Some(1));
+        assert_eq!(game.current_player(), None);
+        assert_eq!(game.register_strike(2, point(1, 1)), Err(TurnError::GameOver));
     }
 
-    return occupied;
-}
+    #[test]
+    fn a_salvo_fires_one_shot_per_ship_afloat() {
+        let mut game = test_game(Rules { salvo: true, ..Rules::default() });
+        assert_eq!(game.shots_this_turn(1), 2);
+        assert_eq!(game.register_strike(1, point(2, 2)), Err(TurnError::WrongShotCount { expected: 2, fired: 1 }));
+        assert_eq!(game.register_salvo(1, &[point(1, 1), point(2, 2), point(3, 3)]), Err(TurnError::WrongShotCount { expected: 2, fired: 3 }));
+
+        let submarine = game.player1_board.ships[0].ship_type.clone();
+        assert_eq!(game.register_salvo(1, &[point(1, 1), point(2, 2)]), Ok(vec![StrikeOutcome::Miss, StrikeOutcome::Miss]));
+        assert_eq!(game.register_salvo(2, &[point(0, 0), point(2, 2)]), Ok(vec![StrikeOutcome::Sunk(submarine), StrikeOutcome::Miss]));
+        assert_eq!(game.shots_this_turn(1), 1);
+        assert_eq!(game.register_salvo(1, &[point(3, 3)]), Ok(vec![StrikeOutcome::Miss]));
+    }
+
+    #[test]
+    fn a_salvo_with_a_bad_target_fires_nothing() {
+        let mut game = test_game(Rules { salvo: true, ..Rules::default() });
+        game.register_salvo(1, &[point(1, 1), point(2, 2)]).unwrap();
+        game.register_salvo(2, &[point(1, 1), point(2, 2)]).unwrap();
+        assert_eq!(game.register_salvo(1, &[point(3, 3), point(3, 3)]), Err(TurnError::RepeatedTarget(point(3, 3))));
+        assert_eq!(game.register_salvo(1, &[point(3, 3), point(2, 2)]), Err(TurnError::RepeatedTarget(point(2, 2))));
+        assert_eq!(game.register_salvo(1, &[point(3, 3), point(5, 3)]), Err(TurnError::OffBoard));
+        assert_eq!(game.register_salvo(1, &[point(-1, 0), point(3, 3)]), Err(TurnError::OffBoard));
+        assert_eq!(game.board(1).open_squares(), 23);
+        assert_eq!(game.current_player(), Some(1));
+    }
 
-pub fn set_boards(player1_placements:Vec<Ship>, player1_occupied: [[bool; BOARD_SIZE]; BOARD_SIZE], player2_placements:Vec<Ship>, player2_occupied: [[bool; BOARD_SIZE]; BOARD_SIZE]) -> GameState {
-    let player1_board = Board{ occupied: player1_occupied, ships: player1_placements.clone(), impacts: HashSet::new() };
-    let player2_board = Board{ occupied: player2_occupied, ships: player2_placements.clone(), impacts: HashSet::new() };
+    #[test]
+    fn a_hit_keeps_the_turn_when_the_rules_say_so() {
+        let mut game = test_game(Rules { shoot_again: true, ..Rules::default() });
+        assert_eq!(game.register_strike(1, point(3, 4)), Ok(StrikeOutcome::Hit));
+        assert_eq!(game.current_player(), Some(1));
+        assert_eq!(game.register_strike(1, point(2, 2)), Ok(StrikeOutcome::Miss));
+        assert_eq!(game.current_player(), Some(2));
+    }
+
+    #[test]
+    fn a_shot_at_water_revealed_earlier_in_the_salvo_replaces_it() {
+        let mut game = test_game(Rules { salvo: true, no_touch: true, reveal_water: true, ..Rules::default() });
+        game.register_salvo(1, &[point(0, 0), point(1, 0)]).unwrap();
+        let board = game.board(1);
+        assert_eq!(board.impacts.len(), 4);
+        assert_eq!(board.open_squares(), 21);
+        assert!(!board.impacts.get(&point(1, 0)).unwrap().revealed);
+        assert!(board.impacts.get(&point(1, 1)).unwrap().revealed);
+    }
 
-    return GameState { player1_board: player1_board, player2_board: player2_board };
+    #[test]
+    fn a_cluster_bomb_that_sinks_a_ship_strikes_each_square_once() {
+        let mut game = test_game(Rules { advanced: true, no_touch: true, reveal_water: true, ..Rules::default() });
+        game.register_action(1, Action::ClusterBomb(point(0, 0))).unwrap();
+        let board = game.board(1);
+        assert_eq!(board.impacts.len(), 4);
+        assert_eq!(board.open_squares(), 21);
+        assert_eq!(board.hit_stats(), (1, 3));
+    }
+
+    #[test]
+    fn weapons_run_out() {
+        let mut game = test_game(Rules { advanced: true, ..Rules::default() });
+        game.register_action(1, Action::Torpedo(2)).unwrap();
+        game.register_strike(2, point(2, 2)).unwrap();
+        assert_eq!(game.register_action(1, Action::Torpedo(3)), Err(TurnError::NoneLeft(Weapon::Torpedo)));
+        assert_eq!(game.register_action(1, Action::Radar(point(5, 0))), Err(TurnError::OffBoard));
+    }
 }
@@ -1,49 +1,28 @@
-use std::{collections::HashSet, io::{self, Write}};
+use rand::{rngs::StdRng, Rng, SeedableRng};
 
-use rand::Rng;
-use strum::IntoEnumIterator;
+use crate::game::Position;
 
-use crate::{enums::{Direction, BOARD_SIZE}, game::{Point, Position}};
+// Every random choice the engine makes (placing ships, the computer's shots, who goes first) is drawn from a single
+// generator per game, so a game started from the same seed and given the same moves plays out exactly the same way.
+pub type GameRng = StdRng;
 
-pub fn input_coordinates() -> Point {
-    print!("Enter coordinates (x,y): ");
-    io::stdout().flush().unwrap();
-    loop {
-        let mut input = String::new();
-        match io::stdin().read_line(&mut input) {
-            Ok(_) => {
-                let coords: Vec<&str> = input.trim().split(',').collect();
-                if coords.len() != 2 {
-                    println!("Invalid input, please enter coordinates in the format x,y");
-                    continue;
-                }
-                let x = match coords[0].parse::<i32>() {
-                    Ok(num) if num >= 0 && num < BOARD_SIZE as i32 => num,
-                    _ => {
-                        println!("Invalid x coordinate, please enter a number between 0 and {}", BOARD_SIZE - 1);
-                        continue;
-                    }
-                };
-                let y = match coords[1].parse::<i32>() {
-                    Ok(num) if num >= 0 && num < BOARD_SIZE as i32 => num,
-                    _ => {
-                        println!("Invalid y coordinate, please enter a number between 0 and {}", BOARD_SIZE - 1);
-                        continue;
-                    }
-                };
-                return Point { x, y };
-            },
-            Err(_) => {
-                println!("Failed to read input, try again.");
-            }
-        }
-    }
+pub fn seeded_rng(seed: u64) -> GameRng {
+    StdRng::seed_from_u64(seed)
 }
 
-pub fn check_position_valid(pos: &Position, occupied: &[[bool; BOARD_SIZE]; BOARD_SIZE]) -> bool {
+// A fresh seed, for games where none was asked for.
+pub fn random_seed() -> u64 {
+    rand::rng().random()
+}
+
+// The occupied grid is indexed as [y][x], so its dimensions double as the board's dimensions.
+pub fn check_position_valid(pos: &Position, occupied: &[Vec<bool>]) -> bool {
+    let height = occupied.len() as i32;
+    let width = occupied.first().map_or(0, |row| row.len()) as i32;
+
     match pos {
         Position::Horizontal { start_x, end_x, y } => {
-            if *y < 0 || *y >= BOARD_SIZE as i32 || *start_x < 0 || *end_x >= BOARD_SIZE as i32 || *start_x > *end_x {
+            if *y < 0 || *y >= height || *start_x < 0 || *end_x >= width || *start_x > *end_x {
                 return false;
             }
             for x in *start_x..=*end_x {
@@ -53,7 +32,7 @@ pub fn check_position_valid(pos: &Position, occupied: &[[bool; BOARD_SIZE]; BOAR
             }
         },
         Position::Vertical { start_y, end_y, x } => {
-            if *x < 0 || *x >= BOARD_SIZE as i32 || *start_y < 0 || *end_y >= BOARD_SIZE as i32 || *start_y > *end_y {
+            if *x < 0 || *x >= width || *start_y < 0 || *end_y >= height || *start_y > *end_y {
                 return false;
             }
             for y in *start_y..=*end_y {
@@ -66,96 +45,45 @@ pub fn check_position_valid(pos: &Position, occupied: &[[bool; BOARD_SIZE]; BOAR
     true
 }
 
-pub fn generate_random_position(ship_length: i32, occupied: &mut [[bool; BOARD_SIZE]; BOARD_SIZE]) -> Position {
-    let mut rng = rand::rng();
-
-    let mut pos;
-    let vertical = rng.random_bool(0.5);
-    if vertical {
-        let start_y = rng.random_range(0..(BOARD_SIZE as i32 - ship_length + 1));
-        let x = rng.random_range(0..BOARD_SIZE) as i32;
-        pos = Position::Vertical { start_y, end_y: start_y + ship_length - 1, x };
-    } else {
-        let start_x = rng.random_range(0..(BOARD_SIZE as i32 - ship_length + 1));
-        let y = rng.random_range(0..BOARD_SIZE) as i32;
-        pos =  Position::Horizontal { start_x, end_x: start_x + ship_length - 1, y };
-    }
-
-    while check_position_valid(&pos, occupied) == false {
-        let vertical = rng.random_bool(0.5);
-        if vertical {
-            let start_y = rng.random_range(0..(BOARD_SIZE as i32 - ship_length + 1));
-            let x = rng.random_range(0..BOARD_SIZE) as i32;
-            pos = Position::Vertical { start_y, end_y: start_y + ship_length - 1, x };
-        } else {
-            let start_x = rng.random_range(0..(BOARD_SIZE as i32 - ship_length + 1));
-            let y = rng.random_range(0..BOARD_SIZE) as i32;
-            pos =  Position::Horizontal { start_x, end_x: start_x + ship_length - 1, y };
-        }
-    }
+// Whether every square around the position, diagonals included, is free. Only the surroundings are checked, so this is
+// used alongside check_position_valid.
+pub fn check_position_isolated(pos: &Position, occupied: &[Vec<bool>]) -> bool {
+    let height = occupied.len() as i32;
+    let width = occupied.first().map_or(0, |row| row.len()) as i32;
 
-    match pos {
-        Position::Horizontal { start_x, end_x, y } => {
-            for x in start_x..=end_x {
-                occupied[y as usize][x as usize] = true;
-            }
-        },
-        Position::Vertical { start_y, end_y, x } => {
-            for y in start_y..=end_y {
-                occupied[y as usize][x as usize] = true;
-            }
-        },
-    }
-
-    return pos;
+    pos.coordinates().iter().all(|coord| {
+        (coord.y - 1..=coord.y + 1).all(|y| (coord.x - 1..=coord.x + 1).all(|x| {
+            x < 0 || y < 0 || x >= width || y >= height || !occupied[y as usize][x as usize]
+        }))
+    })
 }
 
-pub fn input_ship_positon(ship_length: i32) -> Position {
-        let start_pos = input_coordinates();
-        let input_direction;
-        let mut allowed_directions = HashSet::new();
+// Picks a random free position for a ship and marks it as occupied, or returns None if the ship can't fit anywhere. When
+// ships may not touch, positions next to an occupied square are passed over as well.
+pub fn generate_random_position(ship_length: i32, occupied: &mut [Vec<bool>], no_touch: bool, rng: &mut GameRng) -> Option<Position> {
+    let height = occupied.len() as i32;
+    let width = occupied.first().map_or(0, |row| row.len()) as i32;
 
-        for direction in Direction::iter() {
-            if (direction == Direction::Down && start_pos.y + (ship_length - 1) < BOARD_SIZE as i32) 
-            || (direction == Direction::Up && start_pos.y - (ship_length - 1) >= 0)
-            || (direction == Direction::Left && start_pos.x - (ship_length - 1) >= 0)
-            || (direction == Direction::Right && start_pos.x + (ship_length - 1) < BOARD_SIZE as i32) {
-                allowed_directions.insert(direction);
+    let mut candidates = Vec::new();
+    for y in 0..height {
+        for x in 0..width {
+            let horizontal = Position::Horizontal { start_x: x, end_x: x + ship_length - 1, y };
+            let vertical = Position::Vertical { start_y: y, end_y: y + ship_length - 1, x };
+            for pos in [horizontal, vertical] {
+                if check_position_valid(&pos, occupied) && (!no_touch || check_position_isolated(&pos, occupied)) {
+                    candidates.push(pos);
+                }
             }
         }
+    }
+    if candidates.is_empty() {
+        return None;
+    }
 
-        let position;
-        loop {
-            print!("Enter orientation (");
-            for direction in &allowed_directions {
-                print!("{} ", direction.direction_to_string());
-            }
-            print!("):");
-
-            io::stdout().flush().unwrap();
-
-            let mut input = String::new();
-            if io::stdin().read_line(&mut input).is_ok() {
-                let answer = input.trim().to_lowercase();
-                match Direction::string_to_direction(&answer) {
-                    Some(dir) => {
-                        if allowed_directions.contains(&dir) {
-                            input_direction = dir;
-                            position = match input_direction {
-                                Direction::Down => Position::Vertical { start_y: start_pos.y, end_y: start_pos.y + (ship_length - 1), x: start_pos.x },
-                                Direction::Up => Position::Vertical { start_y: start_pos.y - (ship_length - 1), end_y: start_pos.y, x: start_pos.x },
-                                Direction::Left => Position::Horizontal { start_x: start_pos.x - (ship_length - 1), end_x: start_pos.x, y: start_pos.y },
-                                Direction::Right => Position::Horizontal { start_x: start_pos.x, end_x: start_pos.x + (ship_length - 1), y: start_pos.y },
-                            };
+    let pos = candidates[rng.random_range(0..candidates.len())];
+    for coord in pos.coordinates() {
+        occupied[coord.y as usize][coord.x as usize] = true;
+    }
 
-                            break;
-                        } else {
-                            println!("Direction not allowed from this position, please choose another.");
-                        }
-                    },
-                    None => { println!("Invalid option, please answer with [l, r, u or d]!") }
-                }
-            }
-        }
-        return position;
+    Some(pos)
 }
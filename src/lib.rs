@@ -0,0 +1,16 @@
+pub mod ai;
+pub mod commitment;
+pub mod enums;
+pub mod fleet;
+pub mod game;
+pub mod helpers;
+pub mod net;
+pub mod notation;
+pub mod record;
+pub mod rules;
+pub mod save;
+pub mod simulation;
+pub mod weapons;
+
+#[cfg(test)]
+mod testing;
@@ -1,16 +1,21 @@
-use std::io::{self, Write};
+use std::{io::{self, Write}, process, time::{SystemTime, UNIX_EPOCH}};
 
-use enums::OpponentChoice;
+use battleship_rust::{ai::{choose_action, choose_salvo, HuntTargetStrategy, ProbabilityStrategy, RandomStrategy, Strategy}, enums::{Choice, Difficulty, OpponentChoice, StartChoice, DEFAULT_BOARD_SIZE, MAX_BOARD_SIZE, MIN_BOARD_SIZE}, fleet::Fleet, game::{Board, GameState, Point, ShipType, StrikeOutcome}, helpers::{random_seed, seeded_rng, GameRng}, notation::Notation, record::{load_record, save_record, GameRecord}, rules::Rules, save::{load_game, save_game, SavedGame, DEFAULT_SAVE_PATH}, weapons::ActionResult};
+use clap::{error::ErrorKind, CommandFactory, Parser};
+use rand::Rng;
 
-use crate::{enums::{Choice, BOARD_SIZE}, game::{auto_place_ships, place_ships, set_boards, Ship}, helpers::input_coordinates};
+use crate::{bench::simulate, cli::{parse_custom_fleet, parse_size, BoardArgs, Cli, Command, HostArgs, Opponent, PlayArgs}, netplay::{host_game, join_game}, render::{detect_renderer, Renderer}, terminal::{announce_action, announce_losses, announce_outcome, announce_outcomes, auto_place_ships, board_lines, describe_action, describe_points, draw_board, finish_turn, hand_over, heatmap_lines, input_turn, place_ships, print_side_by_side, replay, TurnInput}};
 
-pub mod enums;
-pub mod game;
-pub mod helpers;
+mod bench;
+mod cli;
+mod netplay;
+mod render;
+mod terminal;
+mod tui;
 
 fn choose_opponent() -> OpponentChoice {
     loop {
-        print!("Do you want to play against a human or a computer (NOT IMPLEMENTED YET)? ");
+        print!("Do you want to play against a human or a computer? ");
         io::stdout().flush().unwrap();
 
         let mut input = String::new();
@@ -19,9 +24,7 @@ fn choose_opponent() -> OpponentChoice {
                 let answer = input.trim();
                 match answer {
                     "human" => { return OpponentChoice::Human },
-                    "computer" => {
-                        println!("Computer opponents are not implemented yet.");
-                    },
+                    "computer" => { return OpponentChoice::AI },
                     _ => { println!("Invalid option!") }
                 }
             },
@@ -33,6 +36,186 @@ fn choose_opponent() -> OpponentChoice {
     }
 }
 
+fn choose_difficulty() -> Difficulty {
+    loop {
+        print!("Choose the computer's difficulty [easy/medium/hard]: ");
+        io::stdout().flush().unwrap();
+
+        let mut input = String::new();
+        match io::stdin().read_line(&mut input) {
+            Ok(_) => {
+                let answer = input.trim();
+                match answer.to_lowercase().as_str() {
+                    "easy" => { return Difficulty::Easy },
+                    "medium" => { return Difficulty::Medium },
+                    "hard" => { return Difficulty::Hard },
+                    _ => { println!("Invalid option, please answer with [easy/medium/hard]!") }
+                }
+            },
+            Err(_) => {
+                println!("Failed to read input, try again.");
+            }
+        }
+        println!();
+    }
+}
+
+fn choose_fleet() -> Fleet {
+    println!("Available fleets:");
+    for fleet in Fleet::presets() {
+        println!("  {}", fleet);
+    }
+    loop {
+        print!("Choose a fleet [standard/classic/russian/custom], or press Enter for standard: ");
+        io::stdout().flush().unwrap();
+
+        let mut input = String::new();
+        match io::stdin().read_line(&mut input) {
+            Ok(_) => {
+                let answer = input.trim().to_lowercase();
+                match answer.as_str() {
+                    "" | "standard" => { return Fleet::standard() },
+                    "classic" => { return Fleet::classic() },
+                    "russian" => { return Fleet::russian() },
+                    "custom" => {
+                        if let Some(fleet) = input_custom_fleet() {
+                            return fleet;
+                        }
+                    },
+                    _ => { println!("Invalid option, please answer with [standard/classic/russian/custom]!") }
+                }
+            },
+            Err(_) => {
+                println!("Failed to read input, try again.");
+            }
+        }
+        println!();
+    }
+}
+
+fn input_custom_fleet() -> Option<Fleet> {
+    print!("Enter each ship as NAME LENGTH COUNT, separated by commas: ");
+    io::stdout().flush().unwrap();
+
+    let mut input = String::new();
+    if io::stdin().read_line(&mut input).is_err() {
+        println!("Failed to read input, try again.");
+        return None;
+    }
+
+    match parse_custom_fleet(&input) {
+        Ok(fleet) => Some(fleet),
+        Err(err) => {
+            println!("{}.", err);
+            None
+        }
+    }
+}
+
+fn choose_board_size(fleet: &Fleet, no_touch: bool) -> (i32, i32) {
+    loop {
+        print!("Enter the board size as WIDTHxHEIGHT (between {min} and {max}), or press Enter for {default}x{default}: ",
+            min = MIN_BOARD_SIZE, max = MAX_BOARD_SIZE, default = DEFAULT_BOARD_SIZE);
+        io::stdout().flush().unwrap();
+
+        let mut input = String::new();
+        match io::stdin().read_line(&mut input) {
+            Ok(_) => {
+                let size = if input.trim().is_empty() { Some((DEFAULT_BOARD_SIZE, DEFAULT_BOARD_SIZE)) } else { parse_size(&input) };
+                match size {
+                    Some((width, height)) => {
+                        if fleet.fits(width, height, no_touch) {
+                            return (width, height);
+                        }
+                        println!("The {} fleet doesn't fit on a {}x{} board{}, please choose a bigger one.", fleet.name, width, height, kept_apart(no_touch));
+                    },
+                    None => { println!("Invalid size, please enter two numbers between {} and {}, e.g. 8x8 or 20x12.", MIN_BOARD_SIZE, MAX_BOARD_SIZE) }
+                }
+            },
+            Err(_) => {
+                println!("Failed to read input, try again.");
+            }
+        }
+        println!();
+    }
+}
+
+// How a fleet is described when it doesn't fit on a board, depending on whether its ships have to be kept apart.
+fn kept_apart(no_touch: bool) -> &'static str {
+    if no_touch { " with its ships kept apart" } else { "" }
+}
+
+fn choose_notation() -> Notation {
+    loop {
+        print!("Name squares by numbers (e.g. 1,6) or by row letter and column number (e.g. B2)? [numeric/lettered], or press Enter for numeric: ");
+        io::stdout().flush().unwrap();
+
+        let mut input = String::new();
+        match io::stdin().read_line(&mut input) {
+            Ok(_) => {
+                let answer = input.trim();
+                match answer.to_lowercase().as_str() {
+                    "" | "numeric" => { return Notation::Numeric },
+                    "lettered" => { return Notation::Lettered },
+                    _ => { println!("Invalid option, please answer with [numeric/lettered]!") }
+                }
+            },
+            Err(_) => {
+                println!("Failed to read input, try again.");
+            }
+        }
+        println!();
+    }
+}
+
+fn choose_rules() -> Rules {
+    loop {
+        print!("Do you want to fire one shot a turn, or a salvo of one shot for each ship still afloat? [classic/salvo], or press Enter for classic: ");
+        io::stdout().flush().unwrap();
+
+        let mut input = String::new();
+        match io::stdin().read_line(&mut input) {
+            Ok(_) => {
+                let answer = input.trim();
+                match answer.to_lowercase().as_str() {
+                    "" | "classic" => { return Rules::default() },
+                    "salvo" => { return Rules { salvo: true, ..Rules::default() } },
+                    _ => { println!("Invalid option, please answer with [classic/salvo]!") }
+                }
+            },
+            Err(_) => {
+                println!("Failed to read input, try again.");
+            }
+        }
+        println!();
+    }
+}
+
+// Asks a yes/no question, taking an empty answer as the given default.
+fn ask_yes_no(question: &str, default: bool) -> bool {
+    loop {
+        print!("{} [yes/no], or press Enter for {}: ", question, if default { "yes" } else { "no" });
+        io::stdout().flush().unwrap();
+
+        let mut input = String::new();
+        match io::stdin().read_line(&mut input) {
+            Ok(_) => {
+                let answer = input.trim();
+                match answer.to_lowercase().as_str() {
+                    "" => { return default },
+                    "no" => { return false },
+                    "yes" => { return true },
+                    _ => { println!("Invalid option, please answer with [yes/no]!") }
+                }
+            },
+            Err(_) => {
+                println!("Failed to read input, try again.");
+            }
+        }
+        println!();
+    }
+}
+
 fn decide_autoplace(player: i32) -> Choice {
     loop {
         print!("Player {}, do you want to automatically place your ships? ", player);
@@ -56,69 +239,435 @@ fn decide_autoplace(player: i32) -> Choice {
     }
 }
 
-fn main() {
-    println!("Welcome to Battleship, implemented in Rust.");
-    let opponent_choice = choose_opponent();
+fn choose_start() -> StartChoice {
+    loop {
+        print!("Do you want to start a new game, resume a saved one, replay a recorded one, or play over the network? [new/resume/replay/host/join] ");
+        io::stdout().flush().unwrap();
+
+        let mut input = String::new();
+        match io::stdin().read_line(&mut input) {
+            Ok(_) => {
+                let answer = input.trim();
+                match answer.to_lowercase().as_str() {
+                    "new" => { return StartChoice::New },
+                    "resume" => { return StartChoice::Resume },
+                    "replay" => { return StartChoice::Replay },
+                    "host" => { return StartChoice::Host },
+                    "join" => { return StartChoice::Join },
+                    _ => { println!("Invalid option, please answer with [new/resume/replay/host/join]!") }
+                }
+            },
+            Err(_) => {
+                println!("Failed to read input, try again.");
+            }
+        }
+        println!();
+    }
+}
+
+fn input_path(description: &str, default: Option<&str>) -> String {
+    loop {
+        match default {
+            Some(default) => print!("Enter the {} to load, or press Enter for {}: ", description, default),
+            None => print!("Enter the {} to load: ", description),
+        }
+        io::stdout().flush().unwrap();
+
+        let mut input = String::new();
+        match io::stdin().read_line(&mut input) {
+            Ok(_) if !input.trim().is_empty() => return input.trim().to_string(),
+            Ok(_) if let Some(default) = default => return default.to_string(),
+            Ok(_) => { println!("Please enter a file name.") },
+            Err(_) => { println!("Failed to read input, try again.") }
+        }
+    }
+}
+
+fn create_strategy(difficulty: Option<Difficulty>) -> Box<dyn Strategy> {
+    match difficulty {
+        Some(Difficulty::Easy) | None => Box::new(RandomStrategy),
+        Some(Difficulty::Medium) => Box::new(HuntTargetStrategy),
+        Some(Difficulty::Hard) => Box::new(ProbabilityStrategy::default()),
+    }
+}
+
+// Settles the fleet, board size, notation and rules for a new game, asking for whichever weren't given on the command
+// line.
+fn choose_board(args: BoardArgs) -> (Fleet, (i32, i32), Notation, Rules) {
+    let fleet = args.fleet.unwrap_or_else(|| {
+        let fleet = choose_fleet();
+        println!();
+        fleet
+    });
+    // Ships kept apart take up more room, so this has to be settled before the board size.
+    let no_touch = args.no_touch.unwrap_or_else(|| {
+        let no_touch = ask_yes_no("Do ships have to be kept apart, not touching even at the corners?", false);
+        println!();
+        no_touch
+    });
+    // Water can only be revealed when nothing may lie next to a wreck, so it's only offered when ships are kept apart.
+    let reveal_water = no_touch && args.reveal_water.unwrap_or_else(|| {
+        let reveal_water = ask_yes_no("Mark the squares around each sunk ship as water automatically?", false);
+        println!();
+        reveal_water
+    });
+    let size = match args.size {
+        Some((width, height)) if fleet.fits(width, height, no_touch) => (width, height),
+        size => {
+            // Only reachable with a size from the command line when the fleet or the placement rule was chosen
+            // interactively.
+            if let Some((width, height)) = size {
+                println!("The {} fleet doesn't fit on a {}x{} board{}, please choose a bigger one.\n", fleet.name, width, height, kept_apart(no_touch));
+            }
+            let size = choose_board_size(&fleet, no_touch);
+            println!();
+            size
+        },
+    };
+    let notation = args.notation.map_or_else(|| {
+        let notation = choose_notation();
+        println!();
+        notation
+    }, Notation::from);
+    let mut rules = args.rules.map_or_else(|| {
+        let rules = choose_rules();
+        println!();
+        rules
+    }, Rules::from);
+    rules.shoot_again = args.shoot_again.unwrap_or_else(|| {
+        let shoot_again = ask_yes_no("Does a hit earn another turn?", false);
+        println!();
+        shoot_again
+    });
+    rules.advanced = args.advanced.unwrap_or_else(|| {
+        let advanced = ask_yes_no("Play in advanced mode, with radar scans, cluster bombs and torpedoes?", false);
+        println!();
+        advanced
+    });
+    rules.no_touch = no_touch;
+    rules.reveal_water = reveal_water;
+    (fleet, size, notation, rules)
+}
+
+// Asks for everything needed to set up a fresh game that wasn't given on the command line, and has both players place
+// their ships.
+fn new_game(args: PlayArgs, rng: &mut GameRng, renderer: &dyn Renderer) -> SavedGame {
+    // Asking for a difficulty only makes sense against the computer.
+    let opponent_choice = match (args.opponent, args.difficulty) {
+        (Some(opponent), _) => opponent.into(),
+        (None, Some(_)) => OpponentChoice::AI,
+        (None, None) => choose_opponent(),
+    };
     println!("You have chosen to battle a {:?}.\n", opponent_choice);
 
-    let mut player_placements: Vec<Vec<Ship>> = Vec::new();
-    let mut player_occupied: Vec<[[bool; BOARD_SIZE]; BOARD_SIZE]> = Vec::new();
+    // When playing against the computer, it always takes the role of player 2.
+    let computer_player = match opponent_choice {
+        OpponentChoice::Human => None,
+        OpponentChoice::AI => Some(2),
+    };
+    let difficulty = computer_player.map(|_| args.difficulty.map_or_else(choose_difficulty, Difficulty::from));
 
-    let player1_placements: Vec<Ship> = Vec::new();
-    player_placements.push(player1_placements);
-    let player2_placements: Vec<Ship> = Vec::new();
-    player_placements.push(player2_placements);
+    let (fleet, (width, height), notation, rules) = choose_board(args.board);
+    let blank = Board::with_rules(width, height, fleet, &rules, notation);
+    let mut player_boards = [blank.clone(), blank];
 
     for i in 1..=2 {
-        let occupied: [[bool; BOARD_SIZE]; BOARD_SIZE];
-        let auto_place = decide_autoplace(i);
-        println!();
+        if computer_player == Some(i) {
+            println!("The computer is placing its ships...\n");
+            player_boards[(i - 1) as usize].place_randomly(rng);
+            continue;
+        }
+
+        if computer_player.is_none() {
+            hand_over(i);
+        }
+        let auto_place = match args.auto_place {
+            Some(auto_place) => auto_place.for_player(i),
+            None => {
+                let auto_place = decide_autoplace(i);
+                println!();
+                auto_place
+            },
+        };
 
         match auto_place {
-            Choice::Yes => { occupied = auto_place_ships(&mut player_placements[(i - 1) as usize]); },
-            Choice::No => { occupied = place_ships(i, &mut player_placements[(i - 1) as usize]); }
+            Choice::Yes => auto_place_ships(&mut player_boards[(i - 1) as usize], rng),
+            Choice::No => place_ships(i, &mut player_boards[(i - 1) as usize], renderer)
+        }
+        if computer_player.is_none() {
+            finish_turn();
         }
-        player_occupied.push(occupied);
     }
 
-    let mut game_state = set_boards(player_placements[0].clone(), player_occupied[0], player_placements[1].clone(), player_occupied[1]);
+    let [player1_board, player2_board] = player_boards;
+    let mut game_state = GameState::new(player1_board, player2_board);
+    game_state.rules = rules;
+    let first_player = rng.random_range(1..=2);
+    game_state.begin(first_player);
+    if computer_player == Some(first_player) {
+        println!("The computer goes first.\n");
+    } else {
+        println!("Player {} goes first.\n", first_player);
+    }
+    let record = GameRecord::new(&game_state);
+    SavedGame::new(game_state, computer_player, difficulty, Some(record))
+}
 
-    while !game_state.all_ships_sunk(1) && !game_state.all_ships_sunk(2) {
-        for i in 1..=2 {
-            println!("Player {}, it's your turn!", i);
-            println!("==========================");
-            if i == 1 {
-                game_state.draw_board(1);
-            } else {
-                game_state.draw_board(2);
-            }
+// Turns the answer to the opening question into the subcommand that would have skipped it.
+fn start_command() -> Command {
+    match choose_start() {
+        StartChoice::New => Command::Play(PlayArgs::default()),
+        StartChoice::Resume => Command::Resume { file: None },
+        StartChoice::Replay => Command::Replay { file: None },
+        StartChoice::Host => Command::Host(HostArgs::default()),
+        StartChoice::Join => Command::Join { address: None, auto_place: None },
+    }
+}
+
+// Rejects combinations of options that can't be played, before any prompts are shown.
+fn check_args(command: &Command) {
+    let (board, play) = match command {
+        Command::Play(args) => (&args.board, Some(args)),
+        Command::Host(args) => (&args.board, None),
+        Command::Simulate(args) => (&BoardArgs { fleet: Some(args.fleet.clone()), size: Some(args.size), notation: None, rules: None, shoot_again: None, no_touch: Some(args.no_touch), reveal_water: Some(args.reveal_water), advanced: None }, None),
+        _ => return,
+    };
+    // Without the placement rule on the command line the size can still be asked for again, so only the looser check
+    // applies here.
+    let no_touch = board.no_touch.unwrap_or(false);
+    if let (Some(fleet), Some((width, height))) = (&board.fleet, board.size) && !fleet.fits(width, height, no_touch) {
+        Cli::command().error(ErrorKind::ArgumentConflict, format!("the {} fleet doesn't fit on a {}x{} board{}", fleet.name, width, height, kept_apart(no_touch))).exit();
+    }
+    if board.reveal_water == Some(true) && board.no_touch != Some(true) {
+        Cli::command().error(ErrorKind::ArgumentConflict, "--reveal-water only applies when ships are kept apart with --no-touch").exit();
+    }
+    if let Some(PlayArgs { opponent: Some(Opponent::Human), difficulty: Some(_), .. }) = play {
+        Cli::command().error(ErrorKind::ArgumentConflict, "--difficulty only applies when playing against the computer").exit();
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+    if let Some(command) = &cli.command {
+        check_args(command);
+    }
+
+    println!("Welcome to Battleship, implemented in Rust.");
+    // Setting BATTLESHIP_DEBUG shows the computer's reasoning (e.g. its heatmap) next to its board on every turn.
+    let debug = std::env::var_os("BATTLESHIP_DEBUG").is_some();
+    let renderer = detect_renderer();
+
+    let command = cli.command.unwrap_or_else(start_command);
+    // Replays don't make any random choices, so there's no seed worth showing for them. A resumed game picks up from a
+    // save that doesn't keep the generator's state, so its seed can't play the whole game again either.
+    let seed = cli.seed.unwrap_or_else(random_seed);
+    if !matches!(command, Command::Replay { .. } | Command::Resume { .. }) {
+        println!("Game seed: {} (run with --seed {} to play this game again)", seed, seed);
+    }
+    let mut rng = seeded_rng(seed);
 
+    let setup = match command {
+        Command::Play(args) => {
+            println!();
+            new_game(args, &mut rng, renderer.as_ref())
+        },
+        // A file named on the command line is only tried once, while one typed in can be corrected.
+        Command::Resume { file: Some(path) } => match load_game(&path) {
+            Ok(saved) => {
+                println!("Game resumed. It's player {}'s turn.\n", saved.game.current_player().unwrap_or(1));
+                saved
+            },
+            Err(err) => {
+                println!("{}.", err);
+                process::exit(1);
+            },
+        },
+        Command::Resume { file: None } => loop {
+            match load_game(&input_path("save file", Some(DEFAULT_SAVE_PATH))) {
+                Ok(saved) => {
+                    println!("Game resumed. It's player {}'s turn.\n", saved.game.current_player().unwrap_or(1));
+                    break saved;
+                },
+                Err(err) => println!("{}.\n", err),
+            }
+        },
+        Command::Replay { file: Some(path) } => {
+            match load_record(&path) {
+                Ok(record) => {
+                    println!();
+                    replay(&record, renderer.as_ref());
+                },
+                Err(err) => {
+                    println!("{}.", err);
+                    process::exit(1);
+                },
+            }
+            return;
+        },
+        Command::Replay { file: None } => {
             loop {
-                let strike_coords = input_coordinates();
+                match load_record(&input_path("game record", None)) {
+                    Ok(record) => {
+                        println!();
+                        replay(&record, renderer.as_ref());
+                        return;
+                    },
+                    Err(err) => println!("{}.\n", err),
+                }
+            }
+        },
+        Command::Simulate(args) => {
+            println!();
+            simulate(args, seed);
+            return;
+        },
+        // Networked games run their own loop, since each side only holds one of the two boards.
+        Command::Host(args) => {
+            println!();
+            if let Err(err) = host_game(args, &mut rng, renderer.as_ref()) {
+                println!("{}.", err);
+            }
+            return;
+        },
+        Command::Join { address, auto_place } => {
+            println!();
+            if let Err(err) = join_game(address, auto_place, &mut rng, renderer.as_ref()) {
+                println!("{}.", err);
+            }
+            return;
+        },
+    };
+    let SavedGame { game: mut game_state, computer_player, difficulty, mut record, .. } = setup;
+    let mut strategy = create_strategy(difficulty);
 
-                if !game_state.already_struck(i, strike_coords) {
-                    game_state.register_strike(i, strike_coords);
-                    break;
+    // Ships each player has lost since their last turn, so they can be told at the start of their next one.
+    let mut losses: [Vec<ShipType>; 2] = Default::default();
+    // Whether the player about to fire has just hit and kept the turn, in which case the device stays with them.
+    let mut firing_again = false;
+
+    while let Some(i) = game_state.current_player() {
+        let shots = game_state.shots_this_turn(i);
+        if computer_player == Some(i) {
+            let board = game_state.board(i);
+            let notation = board.notation;
+            let (salvo, outcomes): (Vec<Point>, Vec<StrikeOutcome>) = match choose_action(strategy.as_mut(), board, &game_state.arsenals[(i - 1) as usize], &mut rng) {
+                Some(action) => {
+                    println!("The computer launches {}.", describe_action(&action, notation));
+                    let result = game_state.register_action(i, action).expect("the computer only uses weapons it has left, aimed at the board");
+                    if let Some(record) = record.as_mut() {
+                        record.record_action(i, &action, &result);
+                    }
+                    match result {
+                        ActionResult::Scan(true) => println!("Its radar picks up one of your ships."),
+                        ActionResult::Scan(false) => println!("Its radar finds nothing."),
+                        ActionResult::Strikes(ref strikes) if strikes.is_empty() => println!("It only reaches squares it had already struck."),
+                        ActionResult::Strikes(_) => {},
+                    }
+                    result.strikes().iter().cloned().unzip()
+                },
+                None => {
+                    let salvo = choose_salvo(strategy.as_mut(), board, shots, &mut rng);
+                    println!("The computer fires at {}.", describe_points(&salvo, notation));
+                    if debug && let Some(heatmap) = strategy.heatmap() {
+                        print_side_by_side(&board_lines(board, renderer.as_ref()), &heatmap_lines(board, heatmap));
+                    }
+                    let outcomes = game_state.register_salvo(i, &salvo).expect("the computer only fires on its own turn, at squares it hasn't struck");
+                    if let Some(record) = record.as_mut() {
+                        record.record_salvo(i, &salvo, &outcomes);
+                    }
+                    (salvo, outcomes)
+                },
+            };
+            for (point, outcome) in salvo.iter().zip(&outcomes) {
+                if salvo.len() > 1 {
+                    print!("{}: ", notation.format(*point));
+                }
+                match outcome.sunk_ship() {
+                    Some(ship_type) => println!("Hit! The computer sank your {}!", ship_type),
+                    None => announce_outcome(outcome),
                 }
-                println!("You have already struck this coordinate. Try again.");
+            }
+            if game_state.current_player() == Some(i) {
+                println!("The computer hit, so it fires again.");
             }
             println!();
+            continue;
+        }
+
+        if computer_player.is_none() && !firing_again {
+            hand_over(i);
+        }
+        println!("Player {}, it's your turn!", i);
+        println!("==========================");
+        announce_losses(&losses[(i - 1) as usize]);
+        losses[(i - 1) as usize].clear();
+        draw_board(game_state.board(i), renderer.as_ref());
+
+        let outcomes = loop {
+            match input_turn(game_state.board(i), shots, &game_state.arsenals[(i - 1) as usize], renderer.as_ref()) {
+                TurnInput::Fire(salvo) => {
+                    let outcomes = game_state.register_salvo(i, &salvo).expect("players only fire on their own turn, at squares they haven't struck");
+                    if let Some(record) = record.as_mut() {
+                        record.record_salvo(i, &salvo, &outcomes);
+                    }
+                    announce_outcomes(&salvo, &outcomes, game_state.board(i).notation);
+                    break outcomes;
+                },
+                TurnInput::Weapon(action) => {
+                    let notation = game_state.board(i).notation;
+                    println!("You launch {}.", describe_action(&action, notation));
+                    let result = game_state.register_action(i, action).expect("players only use weapons they have left, aimed at the board");
+                    if let Some(record) = record.as_mut() {
+                        record.record_action(i, &action, &result);
+                    }
+                    announce_action(&action, &result, notation);
+                    break result.outcomes();
+                },
+                TurnInput::Save(path) => {
+                    match save_game(&path, &SavedGame::new(game_state.clone(), computer_player, difficulty, record.clone())) {
+                        Ok(()) => {
+                            println!("Game saved to {}. Choose to resume a saved game next time to carry on.", path);
+                            return;
+                        },
+                        Err(err) => println!("{}.", err),
+                    }
+                }
+            }
+        };
+        losses[(2 - i) as usize].extend(outcomes.iter().filter_map(StrikeOutcome::sunk_ship));
+        firing_again = game_state.current_player() == Some(i);
+        if firing_again {
+            println!("You hit, so you fire again.");
         }
         println!();
+        if computer_player.is_none() && game_state.winner().is_none() && !firing_again {
+            finish_turn();
+        }
     }
 
-    let winning_player;
-    let hit_stats;
-    if game_state.all_ships_sunk(1) {
-        winning_player = 2;
-        println!("Player 2 wins!");
-        hit_stats = game_state.player2_board.hit_stats();
+    // The loop only ends once a strike has sunk the last ship of a fleet.
+    let winning_player = game_state.winner().expect("the game is over");
+    if computer_player == Some(winning_player) {
+        println!("The computer wins!");
     } else {
-        winning_player = 1;
-        println!("Player 1 wins!");
-        hit_stats = game_state.player1_board.hit_stats();
+        println!("Player {} wins!", winning_player);
     }
+    let hit_stats = game_state.board(winning_player).hit_stats();
 
     println!("Player {}'s hit statistics:", winning_player);
     println!("{} successful hits out of {} total strikes made, with - a {}% hit rate", hit_stats.0, hit_stats.1, hit_stats.0 as f32 / hit_stats.1 as f32 * 100.0);
+
+    // Keep a record of the game so it can be replayed later. Games resumed from saves made before records existed have no
+    // complete history, so there's nothing to write for them.
+    match record {
+        Some(record) => {
+            let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_secs());
+            let path = format!("battleship-{}.record", timestamp);
+            match save_record(&path, &record) {
+                Ok(()) => println!("The game has been recorded to {}. Choose replay at startup to watch it again.", path),
+                Err(err) => println!("The game could not be recorded. {}.", err),
+            }
+        },
+        None => println!("This game was resumed from an older save, so no record of it was kept."),
+    }
 }
@@ -0,0 +1,175 @@
+use core::fmt;
+use std::{io::{self, BufRead, BufReader, Write}, net::{TcpListener, TcpStream}};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{enums::{MAX_BOARD_SIZE, MIN_BOARD_SIZE}, fleet::{Fleet, FleetError}, game::{Point, Position, ShipType, StrikeOutcome}, notation::Notation, rules::Rules, weapons::{Action, ActionResult}};
+
+// Bump this whenever a message changes shape, so mismatched builds refuse to play each other rather than misbehave.
+pub const PROTOCOL_VERSION: u32 = 9;
+
+pub const DEFAULT_PORT: u16 = 7878;
+
+// Messages exchanged between the two players of a networked game. The host (player 1) sends Setup as soon as the other
+// player connects, including the rules and which of them fires first; both sides then place their ships in secret and
+// send Ready, committing to their layout. After that the player whose turn it is sends Strike with every shot of their
+// turn, or Weapon in advanced mode, and the other side, being the only one that knows where its ships are, answers with
+// StrikeResult or WeaponResult. Once the game is over both sides send Reveal, so each can check the other answered honestly.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Message {
+    Setup { version: u32, width: i32, height: i32, fleet: Fleet, notation: Notation, rules: Rules, first_player: i32 },
+    Ready { commitment: String },
+    Strike(Vec<Point>),
+    StrikeResult(Vec<(Point, StrikeOutcome)>), // The outcome of each shot, in the order they were sent
+    Weapon(Action),
+    WeaponResult(ActionResult),
+    Reveal { layout: Vec<(ShipType, Position)>, salt: String },
+}
+
+#[derive(Debug)]
+pub enum NetError {
+    Io(io::Error),
+    Format(serde_json::Error),
+    Disconnected,
+    Unexpected(Message), // A well-formed message arrived that doesn't fit the current stage of the game
+    VersionMismatch { ours: u32, theirs: u32 },
+    InvalidSetup(String), // The host asked for a game that can't be played, for the given reason
+    InvalidFleet(FleetError), // The host's fleet breaks the rules every fleet has to follow
+}
+
+impl fmt::Display for NetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetError::Io(err) => write!(f, "Network error: {}", err),
+            NetError::Format(err) => write!(f, "Received a message that could not be understood: {}", err),
+            NetError::Disconnected => write!(f, "The other player disconnected"),
+            NetError::Unexpected(message) => write!(f, "Received an unexpected message: {:?}", message),
+            NetError::VersionMismatch { ours, theirs } => write!(f, "The other player is using protocol version {}, but this build uses version {}", theirs, ours),
+            NetError::InvalidSetup(reason) => write!(f, "The host set up a game that can't be played: {}", reason),
+            NetError::InvalidFleet(err) => write!(f, "The host chose a fleet that can't be played: {}", err),
+        }
+    }
+}
+
+impl From<io::Error> for NetError {
+    fn from(err: io::Error) -> Self {
+        NetError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for NetError {
+    fn from(err: serde_json::Error) -> Self {
+        NetError::Format(err)
+    }
+}
+
+// Checks the game the host set up before anything is built from it, since the other side of a connection can send
+// anything. The same limits apply as when setting up a game locally.
+pub fn check_setup(width: i32, height: i32, fleet: &Fleet, rules: &Rules, first_player: i32) -> Result<(), NetError> {
+    let sizes = MIN_BOARD_SIZE..=MAX_BOARD_SIZE;
+    if !sizes.contains(&width) || !sizes.contains(&height) {
+        return Err(NetError::InvalidSetup(format!("a {}x{} board is outside the allowed sizes", width, height)));
+    }
+    fleet.validate().map_err(NetError::InvalidFleet)?;
+    // Every class has at least one ship of at least one square, so no more classes than squares can ever fit. Checking
+    // each class before adding the fleet up keeps the totals from overflowing.
+    let squares = width * height;
+    if fleet.classes.is_empty() || fleet.classes.len() as i32 > squares || fleet.classes.iter().any(|(ship_type, count)| {
+        !(1..=width.max(height)).contains(&ship_type.length) || !(1..=squares).contains(count)
+    }) || !fleet.fits(width, height, rules.no_touch) {
+        return Err(NetError::InvalidSetup(format!("the {} fleet doesn't fit on a {}x{} board", fleet.name, width, height)));
+    }
+    // Revealing water around a wreck relies on nothing else lying next to it.
+    if rules.reveal_water && !rules.no_touch {
+        return Err(NetError::InvalidSetup("water can only be revealed around sunk ships when ships are kept apart".to_string()));
+    }
+    if !(1..=2).contains(&first_player) {
+        return Err(NetError::InvalidSetup(format!("there is no player {} to fire first", first_player)));
+    }
+    Ok(())
+}
+
+// A connection to the other player. Messages travel as one JSON document per line.
+pub struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    // Waits for the other player to connect on the given port.
+    pub fn host(port: u16) -> Result<Connection, NetError> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        let (stream, _) = listener.accept()?;
+        Connection::new(stream)
+    }
+
+    // Connects to a player who is hosting at the given address, e.g. "127.0.0.1:7878".
+    pub fn join(address: &str) -> Result<Connection, NetError> {
+        Connection::new(TcpStream::connect(address)?)
+    }
+
+    fn new(stream: TcpStream) -> Result<Connection, NetError> {
+        stream.set_nodelay(true)?;
+        Ok(Connection { reader: BufReader::new(stream.try_clone()?), writer: stream })
+    }
+
+    pub fn send(&mut self, message: &Message) -> Result<(), NetError> {
+        let mut line = serde_json::to_string(message)?;
+        line.push('\n');
+        self.writer.write_all(line.as_bytes())?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    // Blocks until the next message arrives.
+    pub fn receive(&mut self) -> Result<Message, NetError> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(NetError::Disconnected);
+        }
+        Ok(serde_json::from_str(&line)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{game::ShipType, testing::test_fleet};
+
+    #[test]
+    fn playable_setups_are_accepted() {
+        for fleet in Fleet::presets() {
+            assert!(check_setup(10, 10, &fleet, &Rules::default(), 1).is_ok(), "{}", fleet);
+        }
+        let rules = Rules { salvo: true, no_touch: true, reveal_water: true, advanced: true, ..Rules::default() };
+        assert!(check_setup(MIN_BOARD_SIZE, MIN_BOARD_SIZE, &test_fleet(), &rules, 2).is_ok());
+    }
+
+    #[test]
+    fn unplayable_setups_are_refused() {
+        let rules = Rules::default();
+        assert!(matches!(check_setup(100000, 5, &Fleet::standard(), &rules, 1), Err(NetError::InvalidSetup(_))));
+        assert!(matches!(check_setup(MIN_BOARD_SIZE, MIN_BOARD_SIZE, &Fleet::standard(), &rules, 1), Err(NetError::InvalidSetup(_))));
+        assert!(matches!(check_setup(10, 10, &Fleet::standard(), &rules, 3), Err(NetError::InvalidSetup(_))));
+        let reveal_without_gaps = Rules { reveal_water: true, ..Rules::default() };
+        assert!(matches!(check_setup(10, 10, &Fleet::standard(), &reveal_without_gaps, 1), Err(NetError::InvalidSetup(_))));
+    }
+
+    #[test]
+    fn fleets_that_could_not_be_built_locally_are_refused() {
+        let with_class = |name: &str, glyph: char| {
+            let mut fleet = test_fleet();
+            fleet.classes.push((ShipType::new(name, 1, glyph), 1));
+            fleet
+        };
+        let refused = |fleet: Fleet| match check_setup(10, 10, &fleet, &Rules::default(), 1) {
+            Err(NetError::InvalidFleet(err)) => err,
+            result => panic!("{:?} was not refused: {:?}", fleet, result),
+        };
+        assert_eq!(refused(with_class("\x1b[2J", 'E')), FleetError::InvalidName("\x1b[2J".to_string()));
+        assert_eq!(refused(with_class("Wide", '船')), FleetError::InvalidGlyph("Wide".to_string()));
+        assert_eq!(refused(with_class("Copy", 'S')), FleetError::DuplicateGlyph("Copy".to_string()));
+        assert_eq!(refused(with_class("SUBMARINE", 'U')), FleetError::DuplicateName("SUBMARINE".to_string()));
+        assert_eq!(refused(Fleet { name: String::new(), ..test_fleet() }), FleetError::InvalidName(String::new()));
+    }
+}
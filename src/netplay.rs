@@ -0,0 +1,279 @@
+use std::io::{self, Write};
+
+use battleship_rust::{ai::afloat_ships, commitment::{commit, generate_salt, verify, CheatError}, enums::Choice, game::{Board, Point, StrikeOutcome}, helpers::GameRng, net::{check_setup, Connection, Message, NetError, DEFAULT_PORT, PROTOCOL_VERSION}, notation::Notation, rules::Rules, weapons::{answer_fits, resolve, Action, ActionResult, Arsenal}};
+use rand::Rng;
+
+use crate::{choose_board, cli::HostArgs, decide_autoplace, render::Renderer, terminal::{announce_action, announce_outcome, announce_outcomes, auto_place_ships, describe_action, describe_points, draw_board, input_strike, place_ships, TurnInput}};
+
+// Networked games: each process only ever knows its own fleet, and the two sides tell each other what every strike hit.
+
+fn input_port() -> u16 {
+    loop {
+        print!("Enter the port to listen on, or press Enter for {}: ", DEFAULT_PORT);
+        io::stdout().flush().unwrap();
+
+        let mut input = String::new();
+        match io::stdin().read_line(&mut input) {
+            Ok(_) if input.trim().is_empty() => return DEFAULT_PORT,
+            Ok(_) => match input.trim().parse::<u16>() {
+                Ok(port) => return port,
+                Err(_) => println!("Invalid port, please enter a number between 0 and {}.", u16::MAX),
+            },
+            Err(_) => { println!("Failed to read input, try again.") }
+        }
+    }
+}
+
+fn input_address() -> String {
+    let default = format!("127.0.0.1:{}", DEFAULT_PORT);
+    print!("Enter the host's address as HOST:PORT, or press Enter for {}: ", default);
+    io::stdout().flush().unwrap();
+
+    let mut input = String::new();
+    match io::stdin().read_line(&mut input) {
+        Ok(_) if !input.trim().is_empty() => input.trim().to_string(),
+        _ => default,
+    }
+}
+
+// Waits for another player to connect, then sets the game up for both sides. The host is player 1 and decides who fires first.
+pub fn host_game(args: HostArgs, rng: &mut GameRng, renderer: &dyn Renderer) -> Result<(), NetError> {
+    let port = args.port.unwrap_or_else(input_port);
+    println!("Waiting for another player to join on port {}...", port);
+    let mut connection = Connection::host(port)?;
+    println!("A player has joined.\n");
+
+    let (fleet, (width, height), notation, rules) = choose_board(args.board);
+    let first_player = rng.random_range(1..=2);
+    connection.send(&Message::Setup { version: PROTOCOL_VERSION, width, height, fleet: fleet.clone(), notation, rules, first_player })?;
+
+    let mut board = Board::with_rules(width, height, fleet, &rules, notation);
+    place_fleet(&mut board, 1, args.auto_place, rng, renderer);
+    play(connection, board, rules, 1, first_player, renderer)
+}
+
+// Connects to a hosted game and plays it with the fleet and board size the host chose. The joining player is player 2.
+pub fn join_game(address: Option<String>, auto_place: Option<bool>, rng: &mut GameRng, renderer: &dyn Renderer) -> Result<(), NetError> {
+    let address = address.unwrap_or_else(input_address);
+    let mut connection = Connection::join(&address)?;
+    println!("Connected, waiting for the host to set up the game...");
+
+    let (mut board, rules, first_player) = match connection.receive()? {
+        Message::Setup { version, width, height, fleet, notation, rules, first_player } if version == PROTOCOL_VERSION => {
+            check_setup(width, height, &fleet, &rules, first_player)?;
+            println!("Playing with the {} fleet on a {}x{} board, under {}.\n", fleet, width, height, rules);
+            let board = Board::with_rules(width, height, fleet, &rules, notation);
+            (board, rules, first_player)
+        },
+        Message::Setup { version, .. } => return Err(NetError::VersionMismatch { ours: PROTOCOL_VERSION, theirs: version }),
+        message => return Err(NetError::Unexpected(message)),
+    };
+
+    place_fleet(&mut board, 2, auto_place, rng, renderer);
+    play(connection, board, rules, 2, first_player, renderer)
+}
+
+// Places the player's ships, automatically or by hand as the command line said, or as they choose if it didn't say.
+fn place_fleet(board: &mut Board, player: i32, auto_place: Option<bool>, rng: &mut GameRng, renderer: &dyn Renderer) {
+    let choice = match auto_place {
+        Some(true) => Choice::Yes,
+        Some(false) => Choice::No,
+        None => decide_autoplace(player),
+    };
+    match choice {
+        Choice::Yes => auto_place_ships(board, rng),
+        Choice::No => place_ships(player, board, renderer),
+    }
+    println!();
+}
+
+// Tells the defending player what the other player's shots did, naming the square first when more than one was fired.
+fn announce_received(points: &[Point], outcomes: &[StrikeOutcome], notation: Notation) {
+    for (point, outcome) in points.iter().zip(outcomes) {
+        if points.len() > 1 {
+            print!("{}: ", notation.format(*point));
+        }
+        match outcome.sunk_ship() {
+            Some(ship_type) => println!("Hit! They sank your {}!", ship_type),
+            None => announce_outcome(outcome),
+        }
+    }
+}
+
+// Plays out a game once this side's ships are placed.
+fn play(mut connection: Connection, mut board: Board, rules: Rules, player: i32, first_player: i32, renderer: &dyn Renderer) -> Result<(), NetError> {
+    // Water the other player's answers reveal isn't checked until the end, so it mustn't stop either side firing there.
+    board.unverified_reveals = true;
+
+    let salt = generate_salt();
+    connection.send(&Message::Ready { commitment: commit(&board.layout(), &salt) })?;
+    println!("Waiting for the other player to place their ships...\n");
+    let their_commitment = match connection.receive()? {
+        Message::Ready { commitment } => commitment,
+        message => return Err(NetError::Unexpected(message)),
+    };
+
+    // Every strike and radar scan made against the other player and the answer they gave, to be checked once their layout
+    // is revealed.
+    let mut strikes = Vec::new();
+    let mut scans = Vec::new();
+    // The special weapons each side has left, indexed by player number minus one. Each side keeps count of both, so a
+    // weapon the other player has run out of can be refused.
+    let mut arsenals = [Arsenal::for_rules(&rules); 2];
+    // What the other player has learned about this board, including any water revealed to them, so their shots can be
+    // checked against the squares they still have left to fire at.
+    let mut their_view = board.blank();
+    if first_player == player {
+        println!("You fire first.\n");
+    } else {
+        println!("The other player fires first.\n");
+    }
+    let mut turn = first_player;
+    let won = loop {
+        if turn == player {
+            println!("Player {}, it's your turn!", player);
+            println!("==========================");
+            draw_board(&board, renderer);
+
+            let shots = rules.shots(board.ships_afloat(), board.open_squares());
+            // Only a wrong answer can leave a fleet afloat once every square has been fired at, and the reveal will show it.
+            if shots == 0 {
+                println!("You have fired at every square, yet the other player's fleet is still afloat.\n");
+                break false;
+            }
+            let outcomes: Vec<StrikeOutcome> = match input_strike(&board, shots, &arsenals[(player - 1) as usize], renderer) {
+                TurnInput::Fire(salvo) => {
+                    connection.send(&Message::Strike(salvo.clone()))?;
+                    let outcomes: Vec<StrikeOutcome> = match connection.receive()? {
+                        Message::StrikeResult(results) if results.iter().map(|(point, _)| *point).eq(salvo.iter().copied()) => {
+                            results.into_iter().map(|(_, outcome)| outcome).collect()
+                        },
+                        message => return Err(NetError::Unexpected(message)),
+                    };
+                    for (point, outcome) in salvo.iter().zip(&outcomes) {
+                        board.record_strike(*point, outcome);
+                        strikes.push((*point, outcome.clone()));
+                    }
+                    announce_outcomes(&salvo, &outcomes, board.notation);
+                    outcomes
+                },
+                TurnInput::Weapon(action) => {
+                    arsenals[(player - 1) as usize].take(action.weapon());
+                    connection.send(&Message::Weapon(action))?;
+                    let result = match connection.receive()? {
+                        Message::WeaponResult(result) if answer_fits(&action, &result, &board) => result,
+                        message => return Err(NetError::Unexpected(message)),
+                    };
+                    board.record_action(&action, &result);
+                    strikes.extend(result.strikes().iter().cloned());
+                    if let (Action::Radar(centre), ActionResult::Scan(contact)) = (action, &result) {
+                        scans.push((centre, *contact));
+                    }
+                    println!("You launch {}.", describe_action(&action, board.notation));
+                    announce_action(&action, &result, board.notation);
+                    result.outcomes()
+                },
+                TurnInput::Save(_) => unreachable!("saving isn't offered"),
+            };
+            if outcomes.iter().any(|outcome| matches!(outcome, StrikeOutcome::GameOver(_))) {
+                println!();
+                break true;
+            }
+            if rules.fires_again(&outcomes) {
+                println!("You hit, so you fire again.\n");
+                continue;
+            }
+            println!();
+        } else {
+            println!("Waiting for the other player to fire...");
+            // They fire one shot for each of their ships our own answers haven't sunk, at squares they haven't tried yet.
+            let shots = rules.shots(afloat_ships(&board).len(), their_view.open_squares());
+            if shots == 0 {
+                println!("The other player has fired at every square, yet your answers say your fleet is still afloat.\n");
+                break false;
+            }
+            let them = (2 - player) as usize;
+            let outcomes: Vec<StrikeOutcome> = match connection.receive()? {
+                Message::Strike(salvo) if salvo.len() == shots && salvo.iter().enumerate().all(|(index, point)| {
+                    board.in_bounds(point) && !their_view.struck(point) && !salvo[..index].contains(point)
+                }) => {
+                    let outcomes: Vec<StrikeOutcome> = salvo.iter().map(|point| board.receive_strike(*point)).collect();
+                    for (point, outcome) in salvo.iter().zip(&outcomes) {
+                        their_view.record_strike(*point, outcome);
+                    }
+                    connection.send(&Message::StrikeResult(salvo.iter().copied().zip(outcomes.iter().cloned()).collect()))?;
+                    println!("The other player fires at {}.", describe_points(&salvo, board.notation));
+                    announce_received(&salvo, &outcomes, board.notation);
+                    outcomes
+                },
+                Message::Weapon(action) if action.in_bounds(&board) && arsenals[them].has(action.weapon()) => {
+                    arsenals[them].take(action.weapon());
+                    let result = resolve(&action, &mut board, &their_view);
+                    their_view.record_action(&action, &result);
+                    connection.send(&Message::WeaponResult(result.clone()))?;
+                    println!("The other player launches {}.", describe_action(&action, board.notation));
+                    match &result {
+                        ActionResult::Scan(true) => println!("Their radar picks up one of your ships."),
+                        ActionResult::Scan(false) => println!("Their radar finds nothing."),
+                        ActionResult::Strikes(strikes) if strikes.is_empty() => println!("It only reaches squares they had already struck."),
+                        ActionResult::Strikes(_) => {
+                            let (points, outcomes): (Vec<Point>, Vec<StrikeOutcome>) = result.strikes().iter().cloned().unzip();
+                            announce_received(&points, &outcomes, board.notation);
+                        },
+                    }
+                    result.outcomes()
+                },
+                message => return Err(NetError::Unexpected(message)),
+            };
+            if outcomes.iter().any(|outcome| matches!(outcome, StrikeOutcome::GameOver(_))) {
+                println!();
+                break false;
+            }
+            if rules.fires_again(&outcomes) {
+                println!("They hit, so they fire again.\n");
+                continue;
+            }
+            println!();
+        }
+        turn = 3 - turn;
+    };
+
+    // Both sides reveal their layout, and check the other's against what they committed to and answered.
+    connection.send(&Message::Reveal { layout: board.layout(), salt })?;
+    let verdict = match connection.receive() {
+        Ok(Message::Reveal { layout, salt }) => verify(&their_commitment, &layout, &salt, board.blank(), &strikes, &scans),
+        Ok(message) => return Err(NetError::Unexpected(message)),
+        Err(err) => {
+            println!("The other player never revealed their layout, so their answers can't be verified.");
+            return Err(err);
+        },
+    };
+
+    match verdict {
+        Ok(()) => {
+            if won {
+                let hit_stats = board.hit_stats();
+                println!("You win!");
+                println!("{} successful hits out of {} total strikes made, with - a {}% hit rate", hit_stats.0, hit_stats.1, hit_stats.0 as f32 / hit_stats.1 as f32 * 100.0);
+            } else {
+                println!("The other player wins!");
+            }
+            println!("The other player's layout matches their commitment and every answer they gave.");
+        },
+        // The engine names squares numerically, so wrong answers are described here in the game's notation instead.
+        Err(CheatError::WrongOutcome { point, reported, actual }) => {
+            println!("Cheating detected! The strike at {} was reported as {}, but it was actually {}.", board.notation.format(point), reported, actual);
+            println!("The other player forfeits the game.");
+        },
+        Err(CheatError::WrongScan { centre, reported }) => {
+            let (claimed, actual) = if reported { ("found a ship", "there was none") } else { ("found nothing", "there was a ship") };
+            println!("Cheating detected! The radar scan around {} was reported to have {}, but {}.", board.notation.format(centre), claimed, actual);
+            println!("The other player forfeits the game.");
+        },
+        Err(err) => {
+            println!("Cheating detected! {}.", err);
+            println!("The other player forfeits the game.");
+        },
+    }
+    Ok(())
+}
@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+
+use crate::game::{Point, Position};
+
+// How squares are named to the players. Numeric is the zero-based "x,y" the game started out with; lettered is the
+// traditional form, with rows lettered from A and columns numbered from 1, so the top left square is A1. Boards are never
+// more than 26 rows tall, so every row gets a single letter.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Notation {
+    #[default]
+    Numeric,
+    Lettered,
+}
+
+impl Notation {
+    pub fn format(&self, point: Point) -> String {
+        match self {
+            Notation::Numeric => format!("({},{})", point.x, point.y),
+            Notation::Lettered => format!("{}{}", self.row_label(point.y), self.column_label(point.x)),
+        }
+    }
+
+    // Names a ship's position by its two ends.
+    pub fn format_position(&self, pos: &Position) -> String {
+        let coords = pos.coordinates();
+        match (coords.first(), coords.last()) {
+            (Some(start), Some(end)) if start != end => format!("{} to {}", self.format(*start), self.format(*end)),
+            (Some(start), _) => self.format(*start),
+            _ => String::new(),
+        }
+    }
+
+    pub fn column_label(&self, x: i32) -> String {
+        match self {
+            Notation::Numeric => x.to_string(),
+            Notation::Lettered => (x + 1).to_string(),
+        }
+    }
+
+    pub fn row_label(&self, y: i32) -> String {
+        match self {
+            Notation::Numeric => y.to_string(),
+            Notation::Lettered => char::from(b'A' + y as u8).to_string(),
+        }
+    }
+
+    // How a player should type a square, for prompts.
+    pub fn example(&self) -> &'static str {
+        match self {
+            Notation::Numeric => "x,y",
+            Notation::Lettered => "e.g. B7",
+        }
+    }
+
+    // Reads a square in either notation, whichever is in use: "3,4" is always numeric and "B7" (or "b7") always lettered.
+    // The result isn't checked against the board's size.
+    pub fn parse(input: &str) -> Option<Point> {
+        let input = input.trim();
+        if let Some((x, y)) = input.split_once(',') {
+            return Some(Point { x: x.trim().parse().ok()?, y: y.trim().parse().ok()? });
+        }
+
+        let mut chars = input.chars();
+        let row = chars.next()?.to_ascii_uppercase();
+        if !row.is_ascii_uppercase() {
+            return None;
+        }
+        let column: i32 = chars.as_str().trim().parse().ok()?;
+        Some(Point { x: column - 1, y: (row as u8 - b'A') as i32 })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::point;
+
+    #[test]
+    fn parses_numeric_squares() {
+        assert_eq!(Notation::parse("3,4"), Some(point(3, 4)));
+        assert_eq!(Notation::parse(" 3 , 4 "), Some(point(3, 4)));
+        assert_eq!(Notation::parse("3,x"), None);
+    }
+
+    #[test]
+    fn parses_lettered_squares() {
+        assert_eq!(Notation::parse("B7"), Some(point(6, 1)));
+        assert_eq!(Notation::parse("b7"), Some(point(6, 1)));
+        assert_eq!(Notation::parse(" C10 "), Some(point(9, 2)));
+        assert_eq!(Notation::parse("A1"), Some(point(0, 0)));
+    }
+
+    #[test]
+    fn rejects_anything_else() {
+        for input in ["", "7B", "Z", "B", "BB7", "é7"] {
+            assert_eq!(Notation::parse(input), None, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn formatted_squares_parse_back() {
+        let square = point(9, 25);
+        assert_eq!(Notation::parse(&Notation::Lettered.format(square)), Some(square));
+        assert_eq!(Notation::parse(Notation::Numeric.format(square).trim_matches(['(', ')'])), Some(square));
+    }
+}
@@ -0,0 +1,205 @@
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{fleet::Fleet, game::{Board, GameState, Phase, PlacementError, Point, Position, ShipType, StrikeOutcome}, notation::Notation, rules::Rules, save::SaveError, weapons::{Action, ActionResult}};
+
+pub const RECORD_VERSION: u32 = 7;
+
+// Something that happened during a game, in the order it happened.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameEvent {
+    Placement { player: i32, ship_type: ShipType, pos: Position },
+    Strike { turn: u32, player: i32, point: Point, outcome: StrikeOutcome },
+    Scan { turn: u32, player: i32, centre: Point, contact: bool }, // A radar scan, which strikes nothing
+}
+
+impl GameEvent {
+    // The turn a strike or radar scan was made on, counting from 1. Placements come before the first turn.
+    pub fn turn(&self) -> Option<u32> {
+        match self {
+            GameEvent::Strike { turn, .. } | GameEvent::Scan { turn, .. } => Some(*turn),
+            GameEvent::Placement { .. } => None,
+        }
+    }
+}
+
+// A complete log of a game: how the boards were set up, then every strike made. Replaying the events in order
+// reconstructs the game exactly as it was played.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameRecord {
+    pub version: u32,
+    pub width: i32,
+    pub height: i32,
+    pub fleet: Fleet,
+    #[serde(default)] // Version 1 records predate lettered notation
+    pub notation: Notation,
+    #[serde(default)] // Version 2 records predate rule variants
+    pub rules: Rules,
+    pub events: Vec<GameEvent>,
+}
+
+impl GameRecord {
+    // Starts a record for a game whose boards have just been set up, logging every ship placement.
+    pub fn new(game: &GameState) -> GameRecord {
+        let board = game.board(1);
+        let mut record = GameRecord { version: RECORD_VERSION, width: board.width, height: board.height, fleet: board.fleet.clone(), notation: board.notation, rules: game.rules, events: Vec::new() };
+        for player in 1..=2 {
+            for ship in &game.board(player).ships {
+                record.events.push(GameEvent::Placement { player, ship_type: ship.ship_type.clone(), pos: ship.pos });
+            }
+        }
+        record
+    }
+
+    // Logs a strike. Strikes that were refused because the point had already been struck don't count as a turn.
+    pub fn record_strike(&mut self, player: i32, point: Point, outcome: &StrikeOutcome) {
+        if *outcome == StrikeOutcome::AlreadyStruck {
+            return;
+        }
+        let turn = self.next_turn();
+        self.events.push(GameEvent::Strike { turn, player, point, outcome: outcome.clone() });
+    }
+
+    // Logs every shot of a salvo as part of the same turn.
+    pub fn record_salvo(&mut self, player: i32, points: &[Point], outcomes: &[StrikeOutcome]) {
+        let turn = self.next_turn();
+        for (point, outcome) in points.iter().zip(outcomes) {
+            self.events.push(GameEvent::Strike { turn, player, point: *point, outcome: outcome.clone() });
+        }
+    }
+
+    // Logs a special weapon: every square it struck as part of the same turn, or the radar scan it made.
+    pub fn record_action(&mut self, player: i32, action: &Action, result: &ActionResult) {
+        let turn = self.next_turn();
+        match (action, result) {
+            (_, ActionResult::Strikes(strikes)) => {
+                for (point, outcome) in strikes {
+                    self.events.push(GameEvent::Strike { turn, player, point: *point, outcome: outcome.clone() });
+                }
+            },
+            (Action::Radar(centre), ActionResult::Scan(contact)) => {
+                self.events.push(GameEvent::Scan { turn, player, centre: *centre, contact: *contact });
+            },
+            (_, ActionResult::Scan(_)) => {},
+        }
+    }
+
+    fn next_turn(&self) -> u32 {
+        self.turn_count() + 1
+    }
+
+    // How many turns were taken in the game. Every shot of a salvo, and every square a special weapon struck, is part of
+    // the same turn.
+    pub fn turn_count(&self) -> u32 {
+        self.events.iter().filter_map(GameEvent::turn).max().unwrap_or(0)
+    }
+
+    // The strikes and radar scans made on the given turn, counting from 1.
+    pub fn turn(&self, turn: u32) -> Vec<&GameEvent> {
+        self.events.iter().filter(|event| event.turn() == Some(turn)).collect()
+    }
+
+    // Both players' boards with their ships placed as the record says, under the record's rules. A record file can be
+    // edited by hand, so every placement is checked again.
+    pub fn placed_boards(&self) -> Result<[Board; 2], SaveError> {
+        let blank = Board::with_rules(self.width, self.height, self.fleet.clone(), &self.rules, self.notation);
+        let mut boards = [blank.clone(), blank];
+        for event in &self.events {
+            if let GameEvent::Placement { player, ship_type, pos } = event {
+                let board = boards.get_mut((player - 1) as usize).ok_or(SaveError::InvalidPlacement { player: *player, ship_type: ship_type.clone(), error: PlacementError::NotInFleet })?;
+                board.place_ship(ship_type.clone(), *pos).map_err(|error| SaveError::InvalidPlacement { player: *player, ship_type: ship_type.clone(), error })?;
+            }
+        }
+        Ok(boards)
+    }
+
+    // Rebuilds the game as it stood after the given number of turns (0 being just after placement). Shots from a salvo or
+    // a special weapon are replayed one at a time, so the rebuilt game is always played by classic rules.
+    pub fn state_after(&self, turns: u32) -> Result<GameState, SaveError> {
+        let [player1_board, player2_board] = self.placed_boards()?;
+        let mut game = GameState::new(player1_board, player2_board);
+        // Strikes set whose turn it is below, so it doesn't matter who is said to go first.
+        game.begin(1);
+        for event in &self.events {
+            if event.turn().is_some_and(|turn| turn > turns) {
+                break;
+            }
+            match event {
+                GameEvent::Strike { player, point, .. } => {
+                    // Follow the record rather than the rules about whose turn it is, so any game that was played replays.
+                    game.phase = Phase::PlayerTurn(*player);
+                    let _ = game.register_strike(*player, *point);
+                },
+                GameEvent::Scan { player, centre, contact, .. } => {
+                    let board = if *player == 1 { &mut game.player1_board } else { &mut game.player2_board };
+                    board.record_action(&Action::Radar(*centre), &ActionResult::Scan(*contact));
+                },
+                GameEvent::Placement { .. } => {},
+            }
+        }
+        Ok(game)
+    }
+}
+
+pub fn save_record(path: &str, record: &GameRecord) -> Result<(), SaveError> {
+    fs::write(path, serde_json::to_string_pretty(record)?)?;
+    Ok(())
+}
+
+pub fn load_record(path: &str) -> Result<GameRecord, SaveError> {
+    let record: GameRecord = serde_json::from_str(&fs::read_to_string(path)?)?;
+    if record.version > RECORD_VERSION {
+        return Err(SaveError::UnsupportedVersion { found: record.version, supported: RECORD_VERSION });
+    }
+    record.placed_boards()?;
+    Ok(record)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{point, test_game, test_layout};
+
+    fn record(rules: Rules) -> GameRecord {
+        GameRecord::new(&test_game(rules))
+    }
+
+    #[test]
+    fn a_legal_record_rebuilds_both_boards() {
+        let record = record(Rules::default());
+        let [player1_board, player2_board] = record.placed_boards().unwrap();
+        assert!(player1_board.fleet_placed() && player2_board.fleet_placed());
+        assert_eq!(record.state_after(0).unwrap().current_player(), Some(1));
+    }
+
+    #[test]
+    fn ships_placed_against_the_rules_are_reported() {
+        let mut record = record(Rules { no_touch: true, ..Rules::default() });
+        let [(submarine, _), (destroyer, _)] = test_layout().try_into().unwrap();
+        // Move player 2's destroyer next to their submarine.
+        record.events[3] = GameEvent::Placement { player: 2, ship_type: destroyer.clone(), pos: Position::Horizontal { start_x: 1, end_x: 2, y: 0 } };
+        let error = record.placed_boards().unwrap_err();
+        assert!(matches!(&error, SaveError::InvalidPlacement { player: 2, ship_type, error: PlacementError::Touching(touched) } if *ship_type == destroyer && *touched == submarine), "{:?}", error);
+        assert!(record.state_after(0).is_err());
+    }
+
+    #[test]
+    fn a_salvo_is_replayed_as_one_turn() {
+        let mut game = test_game(Rules { salvo: true, ..Rules::default() });
+        let mut record = GameRecord::new(&game);
+        for (player, salvo) in [(1, [point(1, 1), point(2, 2)]), (2, [point(0, 0), point(3, 3)])] {
+            let outcomes = game.register_salvo(player, &salvo).unwrap();
+            record.record_salvo(player, &salvo, &outcomes);
+        }
+        assert_eq!(record.turn_count(), 2);
+        assert_eq!(record.turn(1).len(), 2);
+
+        let after_first = record.state_after(1).unwrap();
+        assert_eq!(after_first.board(1).impacts.len(), 2);
+        assert!(after_first.board(2).impacts.is_empty());
+        let after_both = record.state_after(2).unwrap();
+        assert_eq!(after_both.board(2).impacts.len(), 2);
+        assert_eq!(after_both.board(1).ships_afloat(), 1);
+    }
+}
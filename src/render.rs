@@ -0,0 +1,105 @@
+use std::io::{self, IsTerminal};
+
+// What occupies a square of a grid, independent of how it ends up being drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cell {
+    Water,
+    Ship(char), // An undamaged part of one of the player's own ships, drawn with its class's glyph
+    Damaged,
+    Miss,
+    Hit,
+    Sunk,
+    Label(char), // Anything else, e.g. a heatmap digit, drawn as is
+    Ghost(char), // Part of a ship that is being positioned but hasn't been placed yet
+    Blocked, // Part of a ship being positioned where it can't go
+    Targeted, // A square picked for the salvo being aimed, which hasn't been fired at yet
+    Contact, // An unstruck square where a radar scan found that a ship might be
+    Hint, // Water the opponent's answers revealed, which can't be trusted until the end of the game
+}
+
+impl Cell {
+    pub fn glyph(&self) -> char {
+        match self {
+            Cell::Water => '.',
+            Cell::Ship(glyph) | Cell::Label(glyph) | Cell::Ghost(glyph) => *glyph,
+            Cell::Damaged => '†',
+            Cell::Miss => 'o',
+            Cell::Hit => 'X',
+            Cell::Sunk => '#',
+            Cell::Blocked => '!',
+            Cell::Targeted => '+',
+            Cell::Contact => '?',
+            Cell::Hint => '~',
+        }
+    }
+}
+
+// Turns grid squares into text. Every square must come out one character wide on screen, whatever escape codes the
+// renderer wraps around it, so grids can still be laid out by counting characters.
+pub trait Renderer {
+    // Draws a square. Highlighted squares, such as the last shot fired at a grid, should stand out.
+    fn cell(&self, cell: Cell, highlighted: bool) -> String;
+}
+
+// Draws every square as its bare glyph, for terminals without colour and for output that isn't going to a terminal.
+pub struct PlainRenderer;
+
+impl Renderer for PlainRenderer {
+    fn cell(&self, cell: Cell, _highlighted: bool) -> String {
+        cell.glyph().to_string()
+    }
+}
+
+// Colours squares with ANSI escape codes: water blue, ships grey, hits red, misses white and sunk wrecks on a red
+// background. Highlighted squares are shown in reverse video.
+pub struct AnsiRenderer;
+
+impl Renderer for AnsiRenderer {
+    fn cell(&self, cell: Cell, highlighted: bool) -> String {
+        let style = match cell {
+            Cell::Water => "34",
+            Cell::Ship(_) => "90",
+            Cell::Damaged => "31",
+            Cell::Miss => "97",
+            Cell::Hit => "1;91",
+            Cell::Sunk => "1;97;41",
+            Cell::Label(_) => "0",
+            Cell::Ghost(_) => "1;92",
+            Cell::Blocked => "1;93;41",
+            Cell::Targeted => "1;93",
+            Cell::Contact => "1;95",
+            Cell::Hint => "36",
+        };
+        let highlight = if highlighted { ";7" } else { "" };
+        format!("\x1B[{}{}m{}\x1B[0m", style, highlight, cell.glyph())
+    }
+}
+
+// Colour is used when printing straight to a terminal, unless the user has opted out by setting NO_COLOR.
+pub fn detect_renderer() -> Box<dyn Renderer> {
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    if io::stdout().is_terminal() && !no_color {
+        Box::new(AnsiRenderer)
+    } else {
+        Box::new(PlainRenderer)
+    }
+}
+
+// The number of characters a line takes up on screen, not counting ANSI escape codes.
+pub fn visible_width(line: &str) -> usize {
+    let mut width = 0;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1B' {
+            // Skip to the letter that ends the escape sequence.
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            width += 1;
+        }
+    }
+    width
+}
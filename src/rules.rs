@@ -0,0 +1,54 @@
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::StrikeOutcome;
+
+// The rules a game is played by. They're chosen when the game is set up and apply to both players alike.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rules {
+    pub salvo: bool, // Each turn, fire one shot for every ship still afloat, all chosen before any of them land
+    #[serde(default)] // Version 3 records predate shooting again
+    pub shoot_again: bool, // A turn with a hit in it is followed by another turn for the same player
+    #[serde(default)] // Version 4 records predate keeping ships apart
+    pub no_touch: bool, // Ships may not be placed next to each other, not even diagonally
+    #[serde(default)] // Version 5 records predate revealing water
+    pub reveal_water: bool, // Once a ship is sunk, the squares around it are marked as water; only used with no_touch
+    #[serde(default)] // Version 6 records predate advanced mode
+    pub advanced: bool, // Each player also gets a few special weapons, each of which takes up a whole turn
+}
+
+impl Rules {
+    // How many shots a player fires in a turn, given how many of their own ships are still afloat and how many squares
+    // they have left to fire at.
+    pub fn shots(&self, ships_afloat: usize, open_squares: usize) -> usize {
+        if self.salvo { ships_afloat.min(open_squares) } else { 1.min(open_squares) }
+    }
+
+    // Whether the player who just fired a turn with the given outcomes keeps the turn rather than passing it on.
+    pub fn fires_again(&self, outcomes: &[StrikeOutcome]) -> bool {
+        self.shoot_again && outcomes.iter().any(StrikeOutcome::is_hit)
+    }
+}
+
+impl fmt::Display for Rules {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.salvo {
+            write!(f, "salvo rules")?;
+        } else {
+            write!(f, "classic rules")?;
+        }
+        if self.shoot_again {
+            write!(f, ", where a hit earns another turn")?;
+        }
+        if self.no_touch && self.reveal_water {
+            write!(f, ", with ships kept apart and the water around each wreck revealed")?;
+        } else if self.no_touch {
+            write!(f, ", with ships kept apart")?;
+        }
+        if self.advanced {
+            write!(f, ", in advanced mode with special weapons")?;
+        }
+        Ok(())
+    }
+}
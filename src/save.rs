@@ -0,0 +1,190 @@
+use core::fmt;
+use std::{fs, io};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{enums::Difficulty, game::{GameState, PlacementError, ShipType}, record::GameRecord};
+
+// Bump this whenever the saved layout changes, and teach `migrate` how to bring the previous version up to date, so that
+// games saved by older builds can still be resumed.
+pub const SAVE_VERSION: u32 = 10;
+
+pub const DEFAULT_SAVE_PATH: &str = "battleship.save";
+
+// Everything needed to pick a game back up exactly where it stopped. Computer strategies work everything out from the
+// board each turn, so remembering which player is the computer and how hard it plays is enough to restore it.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SavedGame {
+    pub version: u32,
+    pub game: GameState,
+    pub computer_player: Option<i32>,
+    pub difficulty: Option<Difficulty>,
+    pub record: Option<GameRecord>, // Missing for games first saved before records were kept
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    Format(serde_json::Error),
+    UnsupportedVersion { found: u32, supported: u32 },
+    InvalidPlacement { player: i32, ship_type: ShipType, error: PlacementError }, // A ship in a game record that breaks the placement rules
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Io(err) => write!(f, "Could not access the file: {}", err),
+            SaveError::Format(err) => write!(f, "The file is not valid: {}", err),
+            SaveError::UnsupportedVersion { found, supported } => write!(f, "The file is version {}, but this build only understands up to version {}", found, supported),
+            SaveError::InvalidPlacement { player, ship_type, error } => write!(f, "Player {}'s {} is not placed legally: {}", player, ship_type, error),
+        }
+    }
+}
+
+impl From<io::Error> for SaveError {
+    fn from(err: io::Error) -> Self {
+        SaveError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for SaveError {
+    fn from(err: serde_json::Error) -> Self {
+        SaveError::Format(err)
+    }
+}
+
+impl SavedGame {
+    pub fn new(game: GameState, computer_player: Option<i32>, difficulty: Option<Difficulty>, record: Option<GameRecord>) -> SavedGame {
+        SavedGame { version: SAVE_VERSION, game, computer_player, difficulty, record }
+    }
+}
+
+pub fn save_game(path: &str, saved: &SavedGame) -> Result<(), SaveError> {
+    let contents = serde_json::to_string_pretty(saved)?;
+    fs::write(path, contents)?;
+    Ok(())
+}
+
+pub fn load_game(path: &str) -> Result<SavedGame, SaveError> {
+    let contents = fs::read_to_string(path)?;
+    let value: Value = serde_json::from_str(&contents)?;
+    let version = value.get("version").and_then(Value::as_u64).unwrap_or(0) as u32;
+    if version > SAVE_VERSION {
+        return Err(SaveError::UnsupportedVersion { found: version, supported: SAVE_VERSION });
+    }
+
+    let mut saved: SavedGame = serde_json::from_value(migrate(value, version)?)?;
+    saved.version = SAVE_VERSION;
+    Ok(saved)
+}
+
+// Upgrades a save from an older version to the current layout, one version at a time.
+fn migrate(mut value: Value, version: u32) -> Result<Value, SaveError> {
+    match version {
+        SAVE_VERSION => Ok(value),
+        // Version 1 predates game records, so there's no history to carry over.
+        1 => {
+            value["record"] = Value::Null;
+            migrate(value, 2)
+        },
+        // Version 2 didn't track the strikes each board received, but they are exactly the strikes the other board made.
+        2 => {
+            for (board, other) in [("player1_board", "player2_board"), ("player2_board", "player1_board")] {
+                let received: Vec<Value> = value["game"][other]["impacts"].as_array().into_iter().flatten().map(|impact| impact["coords"].clone()).collect();
+                value["game"][board]["received"] = Value::Array(received);
+            }
+            migrate(value, 3)
+        },
+        // Version 3 only kept whose turn it was. Games are only ever saved mid-turn, so that is all the phase needs.
+        3 => {
+            let turn = value["game"]["turn"].take();
+            value["game"]["phase"] = serde_json::json!({ "PlayerTurn": turn });
+            if let Some(game) = value["game"].as_object_mut() {
+                game.remove("turn");
+            }
+            migrate(value, 4)
+        },
+        // Version 4 always named squares by their numeric coordinates.
+        4 => {
+            for board in ["player1_board", "player2_board"] {
+                value["game"][board]["notation"] = Value::from("Numeric");
+            }
+            migrate(value, 5)
+        },
+        // Version 5 only had the classic rules.
+        5 => {
+            value["game"]["rules"] = serde_json::json!({ "salvo": false });
+            migrate(value, 6)
+        },
+        // Version 6 always passed the turn on after every shot.
+        6 => {
+            value["game"]["rules"]["shoot_again"] = Value::from(false);
+            migrate(value, 7)
+        },
+        // Version 7 let ships be placed right next to each other.
+        7 => {
+            value["game"]["rules"]["no_touch"] = Value::from(false);
+            for board in ["player1_board", "player2_board"] {
+                value["game"][board]["no_touch"] = Value::from(false);
+            }
+            migrate(value, 8)
+        },
+        // Version 8 never marked any water the players hadn't fired at.
+        8 => {
+            value["game"]["rules"]["reveal_water"] = Value::from(false);
+            for board in ["player1_board", "player2_board"] {
+                value["game"][board]["reveal_water"] = Value::from(false);
+            }
+            migrate(value, 9)
+        },
+        // Version 9 had no special weapons.
+        9 => {
+            value["game"]["rules"]["advanced"] = Value::from(false);
+            let empty = serde_json::json!({ "radar": 0, "cluster_bomb": 0, "torpedo": 0 });
+            value["game"]["arsenals"] = Value::Array(vec![empty.clone(), empty]);
+            for board in ["player1_board", "player2_board"] {
+                value["game"][board]["contacts"] = serde_json::json!([]);
+            }
+            migrate(value, 10)
+        },
+        _ => Err(SaveError::UnsupportedVersion { found: version, supported: SAVE_VERSION }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{game::Phase, notation::Notation, rules::Rules, testing::point, weapons::Arsenal};
+
+    // A game saved by the first release that wrote save files, two strikes in.
+    const VERSION_1: &str = r#"{"version":1,"game":{"player1_board":{"width":5,"height":5,"fleet":{"name":"Custom","classes":[[{"name":"Sub","length":1,"glyph":"S"},1],[{"name":"Boat","length":2,"glyph":"B"},1]]},"occupied":[[false,false,false,false,false],[false,false,false,true,false],[false,false,false,false,false],[true,false,false,false,false],[true,false,false,false,false]],"ships":[{"health":[true],"pos":{"Vertical":{"start_y":1,"end_y":1,"x":3}},"ship_type":{"name":"Sub","length":1,"glyph":"S"}},{"health":[true,true],"pos":{"Vertical":{"start_y":3,"end_y":4,"x":0}},"ship_type":{"name":"Boat","length":2,"glyph":"B"}}],"impacts":[{"coords":{"x":1,"y":1},"hit":false,"sunk":null},{"coords":{"x":0,"y":0},"hit":false,"sunk":null}]},"player2_board":{"width":5,"height":5,"fleet":{"name":"Custom","classes":[[{"name":"Sub","length":1,"glyph":"S"},1],[{"name":"Boat","length":2,"glyph":"B"},1]]},"occupied":[[false,false,false,false,false],[false,false,false,false,false],[false,true,false,false,false],[false,true,false,false,false],[false,false,false,true,false]],"ships":[{"health":[true],"pos":{"Horizontal":{"start_x":3,"end_x":3,"y":4}},"ship_type":{"name":"Sub","length":1,"glyph":"S"}},{"health":[true,true],"pos":{"Vertical":{"start_y":2,"end_y":3,"x":1}},"ship_type":{"name":"Boat","length":2,"glyph":"B"}}],"impacts":[{"coords":{"x":1,"y":3},"hit":false,"sunk":null},{"coords":{"x":2,"y":2},"hit":false,"sunk":null}]},"turn":1},"computer_player":2,"difficulty":"Easy"}"#;
+
+    #[test]
+    fn version_1_migrates_to_the_current_layout() {
+        let value: Value = serde_json::from_str(VERSION_1).unwrap();
+        let saved: SavedGame = serde_json::from_value(migrate(value, 1).unwrap()).unwrap();
+        let game = saved.game;
+        assert_eq!(game.phase, Phase::PlayerTurn(1));
+        assert_eq!(game.rules, Rules::default());
+        assert_eq!(game.arsenals, <[Arsenal; 2]>::default());
+        assert_eq!(saved.computer_player, Some(2));
+        assert!(saved.record.is_none());
+
+        let received = [point(1, 3), point(2, 2)].into_iter().collect();
+        assert_eq!(game.player1_board.received, received);
+        assert_eq!(game.player1_board.impacts.len(), 2);
+        assert_eq!(game.player2_board.ships.len(), 2);
+        for board in [&game.player1_board, &game.player2_board] {
+            assert_eq!(board.notation, Notation::Numeric);
+            assert!(board.contacts.is_empty());
+            assert!(!board.no_touch && !board.reveal_water);
+        }
+    }
+
+    #[test]
+    fn the_current_version_is_left_alone() {
+        let value = serde_json::json!({ "version": SAVE_VERSION });
+        assert_eq!(migrate(value.clone(), SAVE_VERSION).unwrap(), value);
+    }
+}
@@ -0,0 +1,512 @@
+use std::{collections::HashSet, io::{self, Write}};
+
+use battleship_rust::{ai::{unresolved_hits, Heatmap}, enums::Direction, game::{Board, Point, Position, ShipType, StrikeOutcome}, helpers::GameRng, notation::Notation, record::{GameEvent, GameRecord}, save::DEFAULT_SAVE_PATH, weapons::{Action, ActionResult, Arsenal, Weapon}};
+use strum::IntoEnumIterator;
+
+use crate::{render::{visible_width, Cell, PlainRenderer, Renderer}, tui};
+
+// Everything that talks to the terminal lives here; the engine itself never reads stdin or writes to stdout.
+
+// What a player can enter when it's their turn to fire.
+pub enum TurnInput {
+    Fire(Vec<Point>), // Every shot of the turn, in the order they were picked
+    Weapon(Action), // A special weapon, used in place of the turn's shots
+    Save(String), // Save the game to the given path and stop playing
+}
+
+// In a hot-seat game both players share one terminal, so each player's boards are only drawn once the other player has
+// handed the device over and the screen has been wiped of whatever the previous player was looking at.
+pub fn hand_over(player: i32) {
+    clear_screen();
+    print!("Player {}, press Enter when ready. ", player);
+    io::stdout().flush().unwrap();
+
+    let mut input = String::new();
+    let _ = io::stdin().read_line(&mut input);
+    clear_screen();
+}
+
+// Gives the player who just finished a chance to read the result before their boards are wiped from the screen.
+pub fn finish_turn() {
+    print!("Press Enter to hide your boards and pass the device on. ");
+    io::stdout().flush().unwrap();
+
+    let mut input = String::new();
+    let _ = io::stdin().read_line(&mut input);
+}
+
+fn clear_screen() {
+    // Clear the screen and the scrollback, then move the cursor to the top left corner.
+    print!("\x1B[2J\x1B[3J\x1B[H");
+    io::stdout().flush().unwrap();
+}
+
+pub fn input_coordinates(board: &Board) -> Point {
+    print!("Enter coordinates ({}): ", board.notation.example());
+    io::stdout().flush().unwrap();
+    loop {
+        let mut input = String::new();
+        match io::stdin().read_line(&mut input) {
+            Ok(_) => {
+                match parse_coordinates(input.trim(), board) {
+                    Ok(point) => return point,
+                    Err(message) => println!("{}", message),
+                }
+            },
+            Err(_) => {
+                println!("Failed to read input, try again.");
+            }
+        }
+    }
+}
+
+// Asks where to fire each of the turn's shots, with the cursor on a full-screen terminal or as typed coordinates
+// otherwise. The player may use one of the special weapons left in their arsenal instead. Never returns Save.
+pub fn input_strike(board: &Board, shots: usize, arsenal: &Arsenal, renderer: &dyn Renderer) -> TurnInput {
+    input_shots(board, shots, arsenal, renderer, false)
+}
+
+// Like input_strike, but also lets the player save the game instead: by typing "save" (optionally followed by a file
+// name), or by pressing s on a full-screen terminal.
+pub fn input_turn(board: &Board, shots: usize, arsenal: &Arsenal, renderer: &dyn Renderer) -> TurnInput {
+    input_shots(board, shots, arsenal, renderer, true)
+}
+
+// All of a turn's shots are picked before any of them is fired, so a square can't be picked twice in the same turn.
+// A special weapon can only be chosen before the first shot is picked. With no shots to fire, nothing is asked.
+fn input_shots(board: &Board, shots: usize, arsenal: &Arsenal, renderer: &dyn Renderer, can_save: bool) -> TurnInput {
+    if shots == 0 {
+        return TurnInput::Fire(Vec::new());
+    }
+    if tui::available() {
+        return tui::aim(board, shots, arsenal, renderer, can_save);
+    }
+    if !arsenal.is_empty() {
+        println!("Special weapons left: {}. Enter \"radar\", \"bomb\" or \"torpedo\" followed by a square to use one.", arsenal);
+    }
+    let mut salvo = Vec::new();
+    while salvo.len() < shots {
+        if shots > 1 {
+            print!("Shot {} of {}: ", salvo.len() + 1, shots);
+        }
+        if can_save {
+            print!("Enter coordinates ({}), or \"save [file]\" to save and quit: ", board.notation.example());
+        } else {
+            print!("Enter coordinates ({}): ", board.notation.example());
+        }
+        io::stdout().flush().unwrap();
+
+        let mut input = String::new();
+        if io::stdin().read_line(&mut input).is_err() {
+            println!("Failed to read input, try again.");
+            continue;
+        }
+        let answer = input.trim();
+        if can_save && let Some(rest) = answer.strip_prefix("save") && (rest.is_empty() || rest.starts_with(' ')) {
+            let path = rest.trim();
+            return TurnInput::Save(if path.is_empty() { DEFAULT_SAVE_PATH.to_string() } else { path.to_string() });
+        }
+        if let Some((name, square)) = answer.split_once(' ') && let Some(weapon) = parse_weapon(name) {
+            match parse_coordinates(square.trim(), board) {
+                Ok(_) if !salvo.is_empty() => println!("A special weapon takes the whole turn, so it can't follow a shot."),
+                Ok(_) if !arsenal.has(weapon) => println!("You have no {} left.", weapon),
+                Ok(point) => return TurnInput::Weapon(weapon.aimed_at(point)),
+                Err(message) => println!("{}", message),
+            }
+            continue;
+        }
+        match parse_coordinates(answer, board) {
+            Ok(point) if board.struck(&point) => println!("You have already struck this coordinate."),
+            Ok(point) if salvo.contains(&point) => println!("You have already picked this coordinate this turn."),
+            Ok(point) => salvo.push(point),
+            Err(message) => println!("{}", message),
+        }
+    }
+    TurnInput::Fire(salvo)
+}
+
+// The word typed to use each special weapon.
+fn parse_weapon(name: &str) -> Option<Weapon> {
+    match name {
+        "radar" => Some(Weapon::Radar),
+        "bomb" => Some(Weapon::ClusterBomb),
+        "torpedo" => Some(Weapon::Torpedo),
+        _ => None,
+    }
+}
+
+// Accepts squares in either notation, but always talks back in the one the board uses.
+fn parse_coordinates(input: &str, board: &Board) -> Result<Point, String> {
+    let Some(point) = Notation::parse(input) else {
+        return Err(format!("Invalid input, please enter coordinates in the format {}", board.notation.example()));
+    };
+    if !board.in_bounds(&point) {
+        let last = Point { x: board.width - 1, y: board.height - 1 };
+        return Err(format!("That square is off the board, please enter a square between {} and {}", board.notation.format(Point { x: 0, y: 0 }), board.notation.format(last)));
+    }
+    Ok(point)
+}
+
+pub fn input_ship_positon(board: &Board, ship_length: i32) -> Position {
+        let start_pos = input_coordinates(board);
+        let input_direction;
+        let mut allowed_directions = HashSet::new();
+
+        for direction in Direction::iter() {
+            if (direction == Direction::Down && start_pos.y + (ship_length - 1) < board.height) 
+            || (direction == Direction::Up && start_pos.y - (ship_length - 1) >= 0)
+            || (direction == Direction::Left && start_pos.x - (ship_length - 1) >= 0)
+            || (direction == Direction::Right && start_pos.x + (ship_length - 1) < board.width) {
+                allowed_directions.insert(direction);
+            }
+        }
+
+        let position;
+        loop {
+            print!("Enter orientation (");
+            for direction in &allowed_directions {
+                print!("{} ", direction.direction_to_string());
+            }
+            print!("):");
+
+            io::stdout().flush().unwrap();
+
+            let mut input = String::new();
+            if io::stdin().read_line(&mut input).is_ok() {
+                let answer = input.trim().to_lowercase();
+                match Direction::string_to_direction(&answer) {
+                    Some(dir) => {
+                        if allowed_directions.contains(&dir) {
+                            input_direction = dir;
+                            position = match input_direction {
+                                Direction::Down => Position::Vertical { start_y: start_pos.y, end_y: start_pos.y + (ship_length - 1), x: start_pos.x },
+                                Direction::Up => Position::Vertical { start_y: start_pos.y - (ship_length - 1), end_y: start_pos.y, x: start_pos.x },
+                                Direction::Left => Position::Horizontal { start_x: start_pos.x - (ship_length - 1), end_x: start_pos.x, y: start_pos.y },
+                                Direction::Right => Position::Horizontal { start_x: start_pos.x, end_x: start_pos.x + (ship_length - 1), y: start_pos.y },
+                            };
+
+                            break;
+                        } else {
+                            println!("Direction not allowed from this position, please choose another.");
+                        }
+                    },
+                    None => { println!("Invalid option, please answer with [l, r, u or d]!") }
+                }
+            }
+        }
+        position
+}
+
+pub fn place_ships(player: i32, board: &mut Board, renderer: &dyn Renderer) {
+    if tui::available() {
+        tui::place_ships(player, board, renderer);
+        return;
+    }
+    println!("Player {:?}, place your ships.\n=============================", player);
+
+    for ship_type in board.fleet.ship_types() {
+        let ship_length = ship_type.length;
+
+        for line in ship_lines(board, renderer) {
+            println!("{}", line);
+        }
+        loop {
+            println!("Place your {} (length {})", ship_type, ship_length);
+            let position = input_ship_positon(board, ship_length);
+            match board.place_ship(ship_type.clone(), position) {
+                Ok(()) => break,
+                Err(err) => println!("{}, please choose another position.", err),
+            }
+        }
+        println!();
+    }
+}
+
+pub fn auto_place_ships(board: &mut Board, rng: &mut GameRng) {
+    println!("Automatically placing ships...\n=============================");
+
+    board.place_randomly(rng);
+    for ship in &board.ships {
+        println!("{} placed at {}", ship.ship_type, board.notation.format_position(&ship.pos));
+    }
+}
+
+// Tells the attacking player what their strike did, in the words the defender would use.
+pub fn announce_outcome(outcome: &StrikeOutcome) {
+    match outcome {
+        StrikeOutcome::Miss => println!("Miss!"),
+        StrikeOutcome::Hit => println!("Hit!"),
+        StrikeOutcome::Sunk(ship_type) | StrikeOutcome::GameOver(ship_type) => println!("Hit! You sank my {}!", ship_type),
+        StrikeOutcome::AlreadyStruck => println!("You have already struck this coordinate."),
+    }
+}
+
+// Announces each shot of a turn, naming the square first when more than one was fired.
+pub fn announce_outcomes(points: &[Point], outcomes: &[StrikeOutcome], notation: Notation) {
+    for (point, outcome) in points.iter().zip(outcomes) {
+        if points.len() > 1 {
+            print!("{}: ", notation.format(*point));
+        }
+        announce_outcome(outcome);
+    }
+}
+
+// Lists squares the way they'd be said out loud, e.g. "A1, B2 and C3".
+pub fn describe_points(points: &[Point], notation: Notation) -> String {
+    let names: Vec<String> = points.iter().map(|point| notation.format(*point)).collect();
+    match names.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{} and {}", rest.join(", "), last),
+        _ => names.concat(),
+    }
+}
+
+// Names a special weapon and where it was aimed, e.g. "a cluster bomb on B7" or "a torpedo along row B".
+pub fn describe_action(action: &Action, notation: Notation) -> String {
+    match action {
+        Action::Radar(centre) => format!("a radar scan around {}", notation.format(*centre)),
+        Action::ClusterBomb(centre) => format!("a cluster bomb on {}", notation.format(*centre)),
+        Action::Torpedo(row) => format!("a torpedo along row {}", notation.row_label(*row)),
+    }
+}
+
+// Tells the attacking player what their special weapon did. A torpedo or cluster bomb that only reached squares already
+// struck does nothing at all.
+pub fn announce_action(action: &Action, result: &ActionResult, notation: Notation) {
+    match (action, result) {
+        (Action::Radar(centre), ActionResult::Scan(true)) => println!("The radar picks up a ship somewhere around {}.", notation.format(*centre)),
+        (_, ActionResult::Scan(_)) => println!("The radar finds nothing but water there."),
+        (_, ActionResult::Strikes(strikes)) if strikes.is_empty() => println!("It only reaches squares that were already struck."),
+        (_, ActionResult::Strikes(_)) => {
+            let (points, outcomes): (Vec<Point>, Vec<StrikeOutcome>) = result.strikes().iter().cloned().unzip();
+            announce_outcomes(&points, &outcomes, notation);
+        },
+    }
+}
+
+// Tells a defending player which of their ships went down since they last looked at the board.
+pub fn announce_losses(losses: &[ShipType]) {
+    for ship_type in losses {
+        println!("Your {} has been sunk!", ship_type);
+    }
+    if !losses.is_empty() {
+        println!();
+    }
+}
+
+// A short description of a strike's outcome, for logs and replays.
+pub fn describe_outcome(outcome: &StrikeOutcome) -> String {
+    match outcome {
+        StrikeOutcome::Miss => "Miss".to_string(),
+        StrikeOutcome::Hit => "Hit".to_string(),
+        StrikeOutcome::Sunk(ship_type) => format!("Hit, sinking the {}", ship_type),
+        StrikeOutcome::GameOver(ship_type) => format!("Hit, sinking the {} and winning the game", ship_type),
+        StrikeOutcome::AlreadyStruck => "Already struck".to_string(),
+    }
+}
+
+// Steps through a recorded game one turn at a time, showing both players' boards as they stood after each turn.
+pub fn replay(record: &GameRecord, renderer: &dyn Renderer) {
+    let total = record.turn_count();
+    let mut turn = 0;
+    loop {
+        let game = match record.state_after(turn) {
+            Ok(game) => game,
+            Err(err) => {
+                println!("{}.", err);
+                return;
+            },
+        };
+        println!("Turn {} of {}", turn, total);
+        let events = record.turn(turn);
+        if events.is_empty() {
+            println!("Both players have placed their ships.");
+        }
+        for event in events {
+            match event {
+                GameEvent::Strike { player, point, outcome, .. } => {
+                    println!("Player {} fired at {}: {}", player, record.notation.format(*point), describe_outcome(outcome));
+                },
+                GameEvent::Scan { player, centre, contact, .. } => {
+                    let found = if *contact { "found a ship" } else { "found only water" };
+                    println!("Player {} scanned around {} with radar: {}", player, record.notation.format(*centre), found);
+                },
+                GameEvent::Placement { .. } => {},
+            }
+        }
+        println!();
+
+        let mut player1_lines = vec!["Player 1".to_string()];
+        player1_lines.extend(board_lines(game.board(1), renderer));
+        let mut player2_lines = vec!["Player 2".to_string()];
+        player2_lines.extend(board_lines(game.board(2), renderer));
+        print_side_by_side(&player1_lines, &player2_lines);
+        println!();
+
+        loop {
+            print!("[n]ext, [p]revious, a turn number, or [q]uit: ");
+            io::stdout().flush().unwrap();
+
+            let mut input = String::new();
+            match io::stdin().read_line(&mut input) {
+                Ok(0) => return,
+                Ok(_) => {
+                    let answer = input.trim().to_lowercase();
+                    match answer.as_str() {
+                        "" | "n" if turn < total => { turn += 1; break },
+                        "" | "n" => println!("That was the last turn."),
+                        "p" if turn > 0 => { turn -= 1; break },
+                        "p" => println!("This is the start of the game."),
+                        "q" => return,
+                        _ => match answer.parse::<u32>() {
+                            Ok(number) if number <= total => { turn = number; break },
+                            _ => println!("Invalid option, please enter n, p, q or a turn between 0 and {}.", total),
+                        }
+                    }
+                },
+                Err(_) => {
+                    println!("Failed to read input, try again.");
+                }
+            }
+        }
+        println!();
+    }
+}
+
+pub fn draw_board(board: &Board, renderer: &dyn Renderer) {
+    for line in board_lines(board, renderer) {
+        println!("{}", line);
+    }
+}
+
+// Renders a player's impact grid and ship grid as lines of text, so they can be printed alongside other output.
+pub fn board_lines(board: &Board, renderer: &dyn Renderer) -> Vec<String> {
+    let mut lines = grid_lines("Impacts", &impact_cells(board), board.last_impact, board.notation, renderer);
+    lines.push("-".repeat(visible_width(&lines[1])));
+    lines.extend(ship_lines(board, renderer));
+    lines.push(legend(renderer));
+    lines
+}
+
+pub fn legend(renderer: &dyn Renderer) -> String {
+    let entries = [(Cell::Miss, "miss"), (Cell::Hit, "hit"), (Cell::Sunk, "sunk"), (Cell::Damaged, "damaged")];
+    entries.iter().map(|(cell, name)| format!("{} {}", renderer.cell(*cell, false), name)).collect::<Vec<_>>().join("  ")
+}
+
+// Renders just the player's own ships, e.g. while they are still being placed.
+pub fn ship_lines(board: &Board, renderer: &dyn Renderer) -> Vec<String> {
+    grid_lines("Your ships", &ship_cells(board), board.last_received, board.notation, renderer)
+}
+
+// What the player knows about the opponent's board, from the strikes they have made.
+pub fn impact_cells(board: &Board) -> Vec<Vec<Cell>> {
+    // Hits that can't be pinned on a ship already sunk belong to ships still afloat; every other hit is part of a wreck.
+    let unresolved = unresolved_hits(board);
+    let mut impact_board = vec![vec![Cell::Water; board.width as usize]; board.height as usize];
+    for impact in &board.impacts {
+        impact_board[impact.coords.y as usize][impact.coords.x as usize] = if impact.revealed && board.unverified_reveals {
+            Cell::Hint
+        } else if !impact.hit {
+            Cell::Miss
+        } else if unresolved.contains(&impact.coords) {
+            Cell::Hit
+        } else {
+            Cell::Sunk
+        };
+    }
+    // Radar contacts mark the squares around them that could still hide the ship that was found.
+    for centre in &board.contacts {
+        for point in Action::Radar(*centre).area(board) {
+            if !board.struck(&point) {
+                impact_board[point.y as usize][point.x as usize] = Cell::Contact;
+            }
+        }
+    }
+    impact_board
+}
+
+// The player's own ships, with the damage they have taken and the opponent's misses around them.
+pub fn ship_cells(board: &Board) -> Vec<Vec<Cell>> {
+    let mut ship_board = vec![vec![Cell::Water; board.width as usize]; board.height as usize];
+    for point in &board.received {
+        ship_board[point.y as usize][point.x as usize] = Cell::Miss;
+    }
+    for ship in &board.ships {
+        for (coord, index) in ship.pos.coordinates().iter().zip(0..) {
+            let display_unit = if !ship.health[index] { Cell::Damaged } else { Cell::Ship(ship.ship_type.glyph) };
+            ship_board[coord.y as usize][coord.x as usize] = display_unit;
+        }
+    }
+    ship_board
+}
+
+// Renders a heatmap scaled to single digits (9 being the hottest square), with '-' for squares already fired at.
+pub fn heatmap_lines(board: &Board, heatmap: &Heatmap) -> Vec<String> {
+    let max = heatmap.iter().flatten().copied().max().unwrap_or(0).max(1);
+
+    let mut grid = vec![vec![Cell::Label('-'); board.width as usize]; board.height as usize];
+    for (y, row) in heatmap.iter().enumerate() {
+        for (x, count) in row.iter().enumerate() {
+            if !board.struck(&Point { x: x as i32, y: y as i32 }) {
+                grid[y][x] = Cell::Label(char::from_digit((*count as u128 * 9 / max as u128) as u32, 10).unwrap());
+            }
+        }
+    }
+    grid_lines("Heatmap", &grid, None, board.notation, &PlainRenderer)
+}
+
+// Lays out a titled grid with numbered axes. Labels and cells are padded to the widest label, so boards with ten or
+// more columns or rows stay aligned. The highlighted square (e.g. the last shot fired at the grid) is made to stand out.
+pub fn grid_lines(title: &str, grid: &[Vec<Cell>], highlight: Option<Point>, notation: Notation, renderer: &dyn Renderer) -> Vec<String> {
+    let height = grid.len();
+    let width = grid.first().map_or(0, |row| row.len());
+    let (row_label_width, cell_width) = label_widths(notation, width as i32, height as i32);
+
+    let mut lines = Vec::new();
+    lines.push(title.to_string());
+
+    let mut header = " ".repeat(row_label_width + 1);
+    for x in 0..width {
+        header.push_str(&format!("{:>cell_width$} ", notation.column_label(x as i32)));
+    }
+    lines.push(header.trim_end().to_string());
+
+    for (y, row) in grid.iter().enumerate() {
+        let mut line = format!("{:>row_label_width$} ", notation.row_label(y as i32));
+        for (x, cell) in row.iter().enumerate() {
+            // Every rendered cell is one character wide, so the padding is worked out here rather than by format!, which
+            // would count any escape codes as part of the width.
+            let highlighted = highlight == Some(Point { x: x as i32, y: y as i32 });
+            line.push_str(&" ".repeat(cell_width - 1));
+            line.push_str(&renderer.cell(*cell, highlighted));
+            line.push(' ');
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+// How wide the row labels and the cells of a grid have to be to fit the longest label along each axis.
+pub fn label_widths(notation: Notation, width: i32, height: i32) -> (usize, usize) {
+    let longest = |labels: Vec<String>| labels.iter().map(|label| label.chars().count()).max().unwrap_or(1);
+    let row_label_width = longest((0..height).map(|y| notation.row_label(y)).collect());
+    let cell_width = longest((0..width).map(|x| notation.column_label(x)).collect());
+    (row_label_width, cell_width)
+}
+
+pub fn print_side_by_side(left: &[String], right: &[String]) {
+    for line in side_by_side(left, right) {
+        println!("{}", line);
+    }
+}
+
+// Joins two blocks of lines next to each other, padding the left block so the right one lines up.
+pub fn side_by_side(left: &[String], right: &[String]) -> Vec<String> {
+    let width = left.iter().map(|line| visible_width(line)).max().unwrap_or(0) + 4;
+    let mut lines = Vec::new();
+    for i in 0..left.len().max(right.len()) {
+        let left_line = left.get(i).map(String::as_str).unwrap_or("");
+        let right_line = right.get(i).map(String::as_str).unwrap_or("");
+        lines.push(format!("{}{}{}", left_line, " ".repeat(width - visible_width(left_line)), right_line));
+    }
+    lines
+}
@@ -0,0 +1,40 @@
+use crate::{fleet::Fleet, game::{Board, GameState, Point, Position, ShipType}, notation::Notation, rules::Rules};
+
+// A small game shared by the unit tests: a 5x5 board with a submarine in the top left corner and a destroyer at the right
+// end of the bottom row, so the squares each test touches can be worked out by hand.
+
+pub fn point(x: i32, y: i32) -> Point {
+    Point { x, y }
+}
+
+pub fn test_fleet() -> Fleet {
+    Fleet::new("Test", &[("Submarine".to_string(), 1, 1), ("Destroyer".to_string(), 2, 1)]).unwrap()
+}
+
+pub fn blank_board(rules: &Rules) -> Board {
+    Board::with_rules(5, 5, test_fleet(), rules, Notation::default())
+}
+
+pub fn test_layout() -> Vec<(ShipType, Position)> {
+    let ship_types = test_fleet().ship_types();
+    vec![
+        (ship_types[0].clone(), Position::Horizontal { start_x: 0, end_x: 0, y: 0 }),
+        (ship_types[1].clone(), Position::Horizontal { start_x: 3, end_x: 4, y: 4 }),
+    ]
+}
+
+pub fn placed_board(rules: &Rules) -> Board {
+    let mut board = blank_board(rules);
+    for (ship_type, pos) in test_layout() {
+        board.place_ship(ship_type, pos).unwrap();
+    }
+    board
+}
+
+// A game under the given rules with both fleets placed the same way, where player 1 fires first.
+pub fn test_game(rules: Rules) -> GameState {
+    let mut game = GameState::new(placed_board(&rules), placed_board(&rules));
+    game.rules = rules;
+    assert!(game.begin(1));
+    game
+}
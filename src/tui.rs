@@ -0,0 +1,226 @@
+use std::{io::{self, IsTerminal, Write}, process};
+
+use battleship_rust::{game::{Board, Point, Position}, save::DEFAULT_SAVE_PATH, weapons::{Arsenal, Weapon}};
+use crossterm::{cursor, event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers}, execute, queue, terminal::{self, ClearType}};
+
+use crate::{render::{Cell, Renderer}, terminal::{grid_lines, impact_cells, label_widths, legend, ship_cells, side_by_side, TurnInput}};
+
+// The full-screen interface, used whenever the game is being played at an interactive terminal. Players steer a cursor
+// around the grids with the arrow keys (or hjkl) instead of typing coordinates. Each prompt takes over the screen only
+// while it is waiting for input, so everything else is still printed as ordinary lines in between.
+
+pub fn available() -> bool {
+    io::stdin().is_terminal() && io::stdout().is_terminal()
+}
+
+// Keeps the terminal in raw mode on the alternate screen for as long as it's alive, and puts it back when dropped.
+struct Screen;
+
+impl Screen {
+    fn enter() -> Screen {
+        terminal::enable_raw_mode().expect("failed to switch the terminal to raw mode");
+        execute!(io::stdout(), terminal::EnterAlternateScreen).unwrap();
+        Screen
+    }
+
+    fn draw(&self, lines: &[String], cursor_at: Option<(u16, u16)>) {
+        let mut stdout = io::stdout();
+        queue!(stdout, cursor::Hide, terminal::Clear(ClearType::All), cursor::MoveTo(0, 0)).unwrap();
+        for line in lines {
+            // Raw mode doesn't turn a newline into a carriage return as well.
+            write!(stdout, "{}\r\n", line).unwrap();
+        }
+        if let Some((column, row)) = cursor_at {
+            queue!(stdout, cursor::MoveTo(column, row), cursor::Show).unwrap();
+        }
+        stdout.flush().unwrap();
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+enum Key {
+    Move(i32, i32),
+    Rotate,
+    Confirm,
+    Arm(Option<Weapon>), // Switch to a special weapon, or back to ordinary shots
+    Save,
+    Quit,
+}
+
+fn read_key() -> Key {
+    loop {
+        let Ok(Event::Key(KeyEvent { code, modifiers, kind: KeyEventKind::Press, .. })) = event::read() else {
+            continue;
+        };
+        match code {
+            // Raw mode swallows Ctrl-C, so it has to be handled by hand.
+            KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => return Key::Quit,
+            KeyCode::Left | KeyCode::Char('h') => return Key::Move(-1, 0),
+            KeyCode::Right | KeyCode::Char('l') => return Key::Move(1, 0),
+            KeyCode::Up | KeyCode::Char('k') => return Key::Move(0, -1),
+            KeyCode::Down | KeyCode::Char('j') => return Key::Move(0, 1),
+            KeyCode::Char('r') | KeyCode::Char('R') => return Key::Rotate,
+            KeyCode::Enter | KeyCode::Char(' ') => return Key::Confirm,
+            KeyCode::Char('s') => return Key::Save,
+            KeyCode::Char('1') => return Key::Arm(None),
+            KeyCode::Char('2') => return Key::Arm(Some(Weapon::Radar)),
+            KeyCode::Char('3') => return Key::Arm(Some(Weapon::ClusterBomb)),
+            KeyCode::Char('4') => return Key::Arm(Some(Weapon::Torpedo)),
+            KeyCode::Esc | KeyCode::Char('q') => return Key::Quit,
+            _ => {},
+        }
+    }
+}
+
+// Leaves the game without saving, after putting the terminal back the way it was.
+fn quit(screen: Screen) -> ! {
+    drop(screen);
+    println!("Game abandoned.");
+    process::exit(0);
+}
+
+fn move_cursor(board: &Board, cursor: Point, dx: i32, dy: i32) -> Point {
+    Point { x: (cursor.x + dx).clamp(0, board.width - 1), y: (cursor.y + dy).clamp(0, board.height - 1) }
+}
+
+// Where a square of a grid drawn by grid_lines ends up on screen, given the column and row the grid starts at.
+fn screen_position(board: &Board, point: Point, left: usize, top: usize) -> (u16, u16) {
+    let (row_label_width, cell_width) = label_widths(board.notation, board.width, board.height);
+    let column = left + row_label_width + 1 + point.x as usize * (cell_width + 1) + cell_width - 1;
+    // Grids start with a title line and a line of column numbers.
+    let row = top + 2 + point.y as usize;
+    (column as u16, row as u16)
+}
+
+// Asks the player where to fire each of the turn's shots, with their impacts and their own ships side by side. Already
+// struck squares can't be chosen; picking a square again takes it back, and the turn is fired once every shot has a
+// square. When saving is allowed, pressing s saves to the default file and stops playing. With special weapons left, the
+// number keys arm one in place of the turn's shots, and the squares it would cover are shown around the cursor.
+pub fn aim(board: &Board, shots: usize, arsenal: &Arsenal, renderer: &dyn Renderer, can_save: bool) -> TurnInput {
+    // There's no square left to fire at, so Enter could never complete the turn.
+    if shots == 0 {
+        return TurnInput::Fire(Vec::new());
+    }
+    let screen = Screen::enter();
+    let mut cursor = board.last_impact.unwrap_or(Point { x: board.width / 2, y: board.height / 2 });
+    let mut salvo: Vec<Point> = Vec::new();
+    let mut armed: Option<Weapon> = None;
+    let mut message = String::new();
+
+    loop {
+        let help = if can_save {
+            "Aim with the arrow keys or hjkl, Enter to fire, s to save and quit, q to quit without saving."
+        } else {
+            "Aim with the arrow keys or hjkl, Enter to fire, q to quit."
+        };
+        let mut lines = vec![help.to_string()];
+        lines.push(match armed {
+            Some(weapon) => format!("Aiming a {}. Press 1 to go back to firing shots.", weapon),
+            None if shots > 1 => format!("Shot {} of {}. Pick a square again to take it back.", salvo.len() + 1, shots),
+            None => String::new(),
+        });
+        let mut impact_board = impact_cells(board);
+        let targeted = match armed {
+            Some(weapon) => weapon.aimed_at(cursor).area(board),
+            None => salvo.clone(),
+        };
+        for point in &targeted {
+            impact_board[point.y as usize][point.x as usize] = Cell::Targeted;
+        }
+        let impacts = grid_lines("Impacts", &impact_board, Some(cursor), board.notation, renderer);
+        let ships = grid_lines("Your ships", &ship_cells(board), board.last_received, board.notation, renderer);
+        lines.extend(side_by_side(&impacts, &ships));
+        lines.push(String::new());
+        lines.push(legend(renderer));
+        if !arsenal.is_empty() {
+            lines.push(format!("Special weapons left: {}. Press 2 for radar, 3 for a cluster bomb, 4 for a torpedo.", arsenal));
+        }
+        lines.push(message.clone());
+        screen.draw(&lines, Some(screen_position(board, cursor, 0, 2)));
+
+        match read_key() {
+            Key::Move(dx, dy) => {
+                cursor = move_cursor(board, cursor, dx, dy);
+                message.clear();
+            },
+            Key::Confirm if let Some(weapon) = armed => return TurnInput::Weapon(weapon.aimed_at(cursor)),
+            Key::Confirm if board.struck(&cursor) => message = "You have already struck this coordinate.".to_string(),
+            Key::Confirm if salvo.contains(&cursor) => salvo.retain(|point| *point != cursor),
+            Key::Confirm => {
+                salvo.push(cursor);
+                if salvo.len() == shots {
+                    return TurnInput::Fire(salvo);
+                }
+            },
+            Key::Arm(None) => armed = None,
+            Key::Arm(Some(weapon)) if !arsenal.has(weapon) => message = format!("You have no {} left.", weapon),
+            Key::Arm(Some(_)) if !salvo.is_empty() => message = "A special weapon takes the whole turn, so it can't follow a shot.".to_string(),
+            Key::Arm(weapon) => {
+                armed = weapon;
+                message.clear();
+            },
+            Key::Save if can_save => return TurnInput::Save(DEFAULT_SAVE_PATH.to_string()),
+            Key::Quit => quit(screen),
+            Key::Rotate | Key::Save => {},
+        }
+    }
+}
+
+// Has the player position each ship in the fleet in turn. The ship follows the cursor as a ghost, can be rotated with R,
+// and is drawn in a warning style wherever it can't be placed.
+pub fn place_ships(player: i32, board: &mut Board, renderer: &dyn Renderer) {
+    let screen = Screen::enter();
+    let mut cursor = Point { x: 0, y: 0 };
+    let mut horizontal = true;
+
+    for ship_type in board.fleet.ship_types() {
+        let mut message = String::new();
+        loop {
+            let length = ship_type.length;
+            let pos = if horizontal {
+                Position::Horizontal { start_x: cursor.x, end_x: cursor.x + length - 1, y: cursor.y }
+            } else {
+                Position::Vertical { start_y: cursor.y, end_y: cursor.y + length - 1, x: cursor.x }
+            };
+            // Try the placement on a copy, so the ghost can show whether it would be accepted.
+            let placement = board.clone().place_ship(ship_type.clone(), pos);
+
+            let mut grid = ship_cells(board);
+            for coord in pos.coordinates().iter().filter(|coord| board.in_bounds(coord)) {
+                grid[coord.y as usize][coord.x as usize] = if placement.is_ok() { Cell::Ghost(ship_type.glyph) } else { Cell::Blocked };
+            }
+
+            let mut lines = vec![
+                format!("Player {}, place your {} (length {}).", player, ship_type, length),
+                "Move with the arrow keys or hjkl, R to rotate, Enter to place, q to quit.".to_string(),
+            ];
+            lines.extend(grid_lines("Your ships", &grid, None, board.notation, renderer));
+            lines.push(String::new());
+            lines.push(message.clone());
+            screen.draw(&lines, Some(screen_position(board, cursor, 0, 2)));
+
+            match read_key() {
+                Key::Move(dx, dy) => {
+                    cursor = move_cursor(board, cursor, dx, dy);
+                    message.clear();
+                },
+                Key::Rotate => {
+                    horizontal = !horizontal;
+                    message.clear();
+                },
+                Key::Confirm => match board.place_ship(ship_type.clone(), pos) {
+                    Ok(()) => break,
+                    Err(err) => message = format!("{}, please choose another position.", err),
+                },
+                Key::Quit => quit(screen),
+                Key::Arm(_) | Key::Save => {},
+            }
+        }
+    }
+}
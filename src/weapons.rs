@@ -0,0 +1,269 @@
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{game::{Board, Point, StrikeOutcome}, rules::Rules};
+
+// The special weapons of advanced mode. Using one takes up the player's whole turn, in place of their usual shots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Weapon {
+    Radar,
+    ClusterBomb,
+    Torpedo,
+}
+
+impl fmt::Display for Weapon {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Weapon::Radar => write!(f, "radar scan"),
+            Weapon::ClusterBomb => write!(f, "cluster bomb"),
+            Weapon::Torpedo => write!(f, "torpedo"),
+        }
+    }
+}
+
+impl Weapon {
+    // The weapon aimed at the given square. A torpedo is fired along the square's row.
+    pub fn aimed_at(&self, point: Point) -> Action {
+        match self {
+            Weapon::Radar => Action::Radar(point),
+            Weapon::ClusterBomb => Action::ClusterBomb(point),
+            Weapon::Torpedo => Action::Torpedo(point.y),
+        }
+    }
+}
+
+// A special weapon and where it was aimed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Action {
+    Radar(Point), // Finds out whether any ship lies in the 3x3 area around the point, without damaging it
+    ClusterBomb(Point), // Strikes the point and the four squares orthogonally next to it
+    Torpedo(i32), // Runs along the given row from its left edge, striking each square until it hits a ship
+}
+
+impl Action {
+    pub fn weapon(&self) -> Weapon {
+        match self {
+            Action::Radar(_) => Weapon::Radar,
+            Action::ClusterBomb(_) => Weapon::ClusterBomb,
+            Action::Torpedo(_) => Weapon::Torpedo,
+        }
+    }
+
+    // Whether the action is aimed somewhere on the board. Parts of an area that hang off the edge are simply left out.
+    pub fn in_bounds(&self, board: &Board) -> bool {
+        match self {
+            Action::Radar(point) | Action::ClusterBomb(point) => board.in_bounds(point),
+            Action::Torpedo(row) => (0..board.height).contains(row),
+        }
+    }
+
+    // Every square on the board the action covers, in the order a torpedo would reach them.
+    pub fn area(&self, board: &Board) -> Vec<Point> {
+        let squares: Vec<Point> = match *self {
+            Action::Radar(centre) => (-1..=1).flat_map(|dy| (-1..=1).map(move |dx| Point { x: centre.x + dx, y: centre.y + dy })).collect(),
+            Action::ClusterBomb(centre) => [(0, 0), (0, -1), (-1, 0), (1, 0), (0, 1)].iter().map(|(dx, dy)| Point { x: centre.x + dx, y: centre.y + dy }).collect(),
+            Action::Torpedo(row) => (0..board.width).map(|x| Point { x, y: row }).collect(),
+        };
+        squares.into_iter().filter(|point| board.in_bounds(point)).collect()
+    }
+}
+
+// What an action found out: the outcome of every square it struck, in order, or for a radar scan, whether it found a
+// ship.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ActionResult {
+    Strikes(Vec<(Point, StrikeOutcome)>),
+    Scan(bool),
+}
+
+impl ActionResult {
+    // Every square struck, with its outcome. A radar scan strikes nothing.
+    pub fn strikes(&self) -> &[(Point, StrikeOutcome)] {
+        match self {
+            ActionResult::Strikes(strikes) => strikes,
+            ActionResult::Scan(_) => &[],
+        }
+    }
+
+    pub fn outcomes(&self) -> Vec<StrikeOutcome> {
+        self.strikes().iter().map(|(_, outcome)| outcome.clone()).collect()
+    }
+}
+
+// Carries out an action against the defender's board. Squares the attacker has already struck, or knows to be water, are
+// passed over rather than struck again; the attacker's board says which those are, and it needn't be the one the result
+// is recorded on (e.g. when the defender keeps track of what the attacker knows).
+pub fn resolve(action: &Action, defender: &mut Board, attacker: &Board) -> ActionResult {
+    if let Action::Radar(_) = action {
+        return ActionResult::Scan(action.area(defender).iter().any(|point| defender.occupied[point.y as usize][point.x as usize]));
+    }
+
+    let mut strikes = Vec::new();
+    for point in action.area(defender).into_iter().filter(|point| !attacker.struck(point)) {
+        let outcome = defender.receive_strike(point);
+        let stops = matches!(action, Action::Torpedo(_)) && outcome.is_hit();
+        strikes.push((point, outcome));
+        if stops {
+            break;
+        }
+    }
+    ActionResult::Strikes(strikes)
+}
+
+// Whether the other side's answer to an action is shaped the way the action allows, given what the attacker had struck
+// before firing it. Whether the answers are true is only known once the layout is revealed at the end of the game.
+pub fn answer_fits(action: &Action, result: &ActionResult, attacker: &Board) -> bool {
+    let open: Vec<Point> = action.area(attacker).into_iter().filter(|point| !attacker.struck(point)).collect();
+    match (action, result) {
+        (Action::Radar(_), ActionResult::Scan(_)) => true,
+        (Action::ClusterBomb(_), ActionResult::Strikes(strikes)) => strikes.iter().map(|(point, _)| *point).eq(open.iter().copied()),
+        // A torpedo strikes the open squares of its row in turn, and goes no further than the first hit.
+        (Action::Torpedo(_), ActionResult::Strikes(strikes)) => {
+            let hit_at = strikes.iter().position(|(_, outcome)| outcome.is_hit());
+            let length = hit_at.map_or(open.len(), |index| index + 1);
+            strikes.len() == length && strikes.iter().map(|(point, _)| *point).eq(open.iter().take(length).copied())
+        },
+        _ => false,
+    }
+}
+
+// How many of each special weapon a player has left.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Arsenal {
+    pub radar: u32,
+    pub cluster_bomb: u32,
+    pub torpedo: u32,
+}
+
+impl Arsenal {
+    // What each player starts an advanced game with. Other games have no special weapons at all.
+    pub fn for_rules(rules: &Rules) -> Arsenal {
+        if rules.advanced {
+            Arsenal { radar: 2, cluster_bomb: 2, torpedo: 1 }
+        } else {
+            Arsenal::default()
+        }
+    }
+
+    pub fn count(&self, weapon: Weapon) -> u32 {
+        match weapon {
+            Weapon::Radar => self.radar,
+            Weapon::ClusterBomb => self.cluster_bomb,
+            Weapon::Torpedo => self.torpedo,
+        }
+    }
+
+    pub fn has(&self, weapon: Weapon) -> bool {
+        self.count(weapon) > 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.radar + self.cluster_bomb + self.torpedo == 0
+    }
+
+    // Uses up one of the given weapon, or returns false if there are none left.
+    pub fn take(&mut self, weapon: Weapon) -> bool {
+        let count = match weapon {
+            Weapon::Radar => &mut self.radar,
+            Weapon::ClusterBomb => &mut self.cluster_bomb,
+            Weapon::Torpedo => &mut self.torpedo,
+        };
+        if *count == 0 {
+            return false;
+        }
+        *count -= 1;
+        true
+    }
+}
+
+impl fmt::Display for Arsenal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = |count: u32, one: &str, many: &str| format!("{} {}", count, if count == 1 { one } else { many });
+        write!(f, "{}, {} and {}", count(self.radar, "radar scan", "radar scans"), count(self.cluster_bomb, "cluster bomb", "cluster bombs"), count(self.torpedo, "torpedo", "torpedoes"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{game::Position, testing::{blank_board, point, test_fleet}};
+
+    // The attacker's view of the other board, with one miss already made in the middle row.
+    fn attacker() -> Board {
+        let mut board = blank_board(&Rules::default());
+        board.record_strike(point(1, 2), &StrikeOutcome::Miss);
+        board
+    }
+
+    fn strikes(outcomes: &[(Point, StrikeOutcome)]) -> ActionResult {
+        ActionResult::Strikes(outcomes.to_vec())
+    }
+
+    #[test]
+    fn a_torpedo_stops_at_the_first_ship() {
+        let mut defender = blank_board(&Rules::default());
+        defender.place_ship(test_fleet().ship_types()[1].clone(), Position::Horizontal { start_x: 2, end_x: 3, y: 2 }).unwrap();
+        let result = resolve(&Action::Torpedo(2), &mut defender, &attacker());
+        assert_eq!(result, strikes(&[(point(0, 2), StrikeOutcome::Miss), (point(2, 2), StrikeOutcome::Hit)]));
+        assert!(answer_fits(&Action::Torpedo(2), &result, &attacker()));
+    }
+
+    #[test]
+    fn a_torpedo_answer_must_run_along_the_open_squares_to_the_first_hit() {
+        let torpedo = Action::Torpedo(2);
+        let (miss, hit) = (StrikeOutcome::Miss, StrikeOutcome::Hit);
+        let all_misses = strikes(&[(point(0, 2), miss.clone()), (point(2, 2), miss.clone()), (point(3, 2), miss.clone()), (point(4, 2), miss.clone())]);
+        assert!(answer_fits(&torpedo, &all_misses, &attacker()));
+        assert!(answer_fits(&torpedo, &strikes(&[(point(0, 2), hit.clone())]), &attacker()));
+
+        let past_a_hit = strikes(&[(point(0, 2), hit.clone()), (point(2, 2), miss.clone())]);
+        assert!(!answer_fits(&torpedo, &past_a_hit, &attacker()));
+        let stopped_short = strikes(&[(point(0, 2), miss.clone()), (point(2, 2), miss.clone())]);
+        assert!(!answer_fits(&torpedo, &stopped_short, &attacker()));
+        let through_struck_square = strikes(&[(point(0, 2), miss.clone()), (point(1, 2), hit.clone())]);
+        assert!(!answer_fits(&torpedo, &through_struck_square, &attacker()));
+        assert!(!answer_fits(&torpedo, &ActionResult::Scan(false), &attacker()));
+    }
+
+    #[test]
+    fn a_cluster_bomb_answer_must_cover_its_area_in_order() {
+        let bomb = Action::ClusterBomb(point(0, 0));
+        let miss = StrikeOutcome::Miss;
+        let whole_area = strikes(&[(point(0, 0), miss.clone()), (point(1, 0), miss.clone()), (point(0, 1), miss.clone())]);
+        assert!(answer_fits(&bomb, &whole_area, &attacker()));
+
+        let missing_a_square = strikes(&[(point(0, 0), miss.clone()), (point(1, 0), miss.clone())]);
+        assert!(!answer_fits(&bomb, &missing_a_square, &attacker()));
+        let out_of_order = strikes(&[(point(1, 0), miss.clone()), (point(0, 0), miss.clone()), (point(0, 1), miss.clone())]);
+        assert!(!answer_fits(&bomb, &out_of_order, &attacker()));
+        assert!(!answer_fits(&bomb, &ActionResult::Scan(true), &attacker()));
+    }
+
+    #[test]
+    fn an_unverified_scan_leaves_its_area_open() {
+        let radar = Action::Radar(point(2, 2));
+        let mut trusted = blank_board(&Rules::default());
+        trusted.record_action(&radar, &ActionResult::Scan(false));
+        assert!(trusted.struck(&point(1, 1)));
+        assert_eq!(trusted.open_squares(), 16);
+
+        let mut networked = Board { unverified_reveals: true, ..blank_board(&Rules::default()) };
+        networked.record_action(&radar, &ActionResult::Scan(false));
+        assert!(!networked.struck(&point(1, 1)));
+        assert_eq!(networked.open_squares(), 25);
+        networked.record_strike(point(1, 1), &StrikeOutcome::Hit);
+        assert!(networked.struck(&point(1, 1)));
+        assert_eq!(networked.open_squares(), 24);
+    }
+
+    #[test]
+    fn the_arsenal_is_only_stocked_in_advanced_mode() {
+        assert!(Arsenal::for_rules(&Rules::default()).is_empty());
+        let mut arsenal = Arsenal::for_rules(&Rules { advanced: true, ..Rules::default() });
+        let torpedoes = arsenal.count(Weapon::Torpedo);
+        assert!(torpedoes > 0);
+        assert!((0..torpedoes).all(|_| arsenal.take(Weapon::Torpedo)));
+        assert!(!arsenal.take(Weapon::Torpedo));
+    }
+}